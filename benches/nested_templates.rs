@@ -0,0 +1,40 @@
+#![feature(test)]
+extern crate foil;
+extern crate test;
+
+use foil::compiler::tokenizer::Tokenizer;
+use foil::compiler::parser::Parser;
+use foil::compiler::evaluator::{Evaluator, Env};
+use foil::compiler::models::Output;
+use test::Bencher;
+
+/// Parses and evaluates the input.
+fn eval_str(input: &str) -> Output {
+    let mut tokenizer = Tokenizer::new(input);
+    let mut parser = Parser::new(&mut tokenizer);
+    let ast = parser.next().unwrap().unwrap();
+    Evaluator::without_files(&ast, Env::new()).eval().unwrap()
+}
+
+/// Every layer of the page uses the content of the layer below it twice. Without memoization
+/// the innermost content would be evaluated 2^100 times.
+#[bench]
+fn nested_layouts(b: &mut Bencher) {
+    let input = "
+        let site = set { title = \"Blog\" },
+            layout = fn title body: html! section { h2 { (title) } (if == body \"\" then \"-\" else body) },
+            page = fn depth: if == depth 0 then \"content\" else (layout site.title (page - depth 1))
+        in (page 100)";
+    b.iter(|| eval_str(input));
+}
+
+/// Every binding refers to the previous binding twice.
+#[bench]
+fn nested_bindings(b: &mut Bencher) {
+    let mut input = "let a0 = 1 in ".to_string();
+    for i in 1..101 {
+        input.push_str(&format!("let a{} = - + a{} a{} a{} in ", i, i - 1, i - 1, i - 1));
+    }
+    input.push_str("a100");
+    b.iter(|| eval_str(&input));
+}
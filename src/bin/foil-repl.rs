@@ -1,7 +1,7 @@
 extern crate foil;
 use foil::compiler::tokenizer::Tokenizer;
 use foil::compiler::parser::Parser;
-use foil::compiler::evaluator::{Evaluator, Scope, OpenScope, EvalResult};
+use foil::compiler::evaluator::{Evaluator, Env};
 use foil::compiler::errors::{EvalError, ParseError};
 
 use std::io::{self, BufRead};
@@ -47,14 +47,17 @@ fn main() {
     }
 }
 
-fn eval(text: &str) -> EvalResult {
+/// Evaluates the text and returns the printed output.
+fn eval(text: &str) -> Result<String, EvalError> {
     let mut tokenizer = Tokenizer::new(text);
     let mut parser = Parser::new(&mut tokenizer);
     if let Some(parse_res) = parser.next() {
         match parse_res {
             Ok(ast) => {
-                let scope = OpenScope::new();
-                Evaluator::without_files(&ast,  Scope::Open(&scope)).eval()
+                let evaluator = Evaluator::without_files(&ast, Env::new());
+                let result = evaluator.eval().map(|output| format!("{}", output));
+                evaluator.context.clear_modules();
+                result
             },
             Err(err) => Err(EvalError::Parser(err)),
         }
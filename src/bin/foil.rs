@@ -4,21 +4,35 @@ extern crate tempdir;
 use tempdir::TempDir;
 use std::path::{Path, PathBuf};
 use std::env;
+use std::rc::Rc;
 use fs_extra::dir;
 use std::fs::{create_dir_all};
-use foil::compiler::build_file;
+use foil::compiler::build_file_with_context;
+use foil::compiler::errors::EvalError;
+use foil::compiler::evaluator::{Context, DEFAULT_MAX_DEPTH};
+
+/// The environment variable that holds additional library directories to import files from.
+const FOIL_PATH: &'static str = "FOIL_PATH";
+
+/// The command line options.
+struct Options {
+    index_file: String,
+    max_depth: usize,
+    stats: bool,
+    include_dirs: Vec<PathBuf>,
+}
 
 fn main() {
-    let param = get_parameter();
-    if None == param {
+    let options = get_options();
+    if options.is_none() {
         print_usage();
         return;
     }
-    let param = param.unwrap();
+    let options = options.unwrap();
 
-    let path = to_src_root_path(&param);
+    let path = to_src_root_path(&options.index_file);
     if path == None {
-        eprint!("`{}` is not a file", param);
+        eprint!("`{}` is not a file", options.index_file);
         return;
     }
     let index_file = path.unwrap();
@@ -26,7 +40,14 @@ fn main() {
     let tmp_out_dir = TempDir::new("out").unwrap();
     let tmp_out_dir = tmp_out_dir.path();
 
-    let result = build_file(&index_file, &tmp_out_dir);
+    let mut context = Context::new(options.max_depth);
+    context.root = index_file.parent().map(Path::to_path_buf);
+    context.search_path = get_search_path(&options.include_dirs);
+    let context = Rc::new(context);
+    let result = build_file_with_context(&index_file, &tmp_out_dir, context.clone());
+    if options.stats {
+        print_stats(&context);
+    }
     match result {
         Ok(()) => {
             println!("Copying to output path...");
@@ -41,17 +62,70 @@ fn main() {
             dir::copy(tmp_out_dir, out_root, &opts).unwrap();
             print_build_success();
         },
+        Err(EvalError::RecursionLimit(frames)) => print_recursion_limit(&frames),
+        Err(EvalError::ImportCycle(files)) => print_import_cycle(&files),
+        Err(EvalError::ValueCycle(expr)) => println!("The value of `{}` depends on itself.", expr),
+        Err(EvalError::NotFile(tried)) => print_not_file(&tried),
+        Err(EvalError::InvalidData(file, Some(line), message)) => {
+            println!("{}:{}: {}", file.display(), line, message);
+        },
+        Err(EvalError::InvalidData(file, None, message)) => {
+            println!("{}: {}", file.display(), message);
+        },
         Err(err) => println!("{:?}", err),
     }
 
 }
 
+fn print_stats(context: &Context) {
+    println!("Module cache: {} hits, {} misses", context.cache_hits(), context.cache_misses());
+}
+
 fn print_build_success() {
     println!("Build successfull!");
 }
 
+/// Prints the frames of a `RecursionLimit` error, innermost first. Consecutive repetitions of
+/// the same frame are only printed once.
+fn print_recursion_limit(frames: &Vec<String>) {
+    println!("Maximum evaluation depth exceeded. Use --max-depth to raise the limit.");
+    let mut frames = frames.iter().rev().peekable();
+    while let Some(frame) = frames.next() {
+        let mut count = 1;
+        while frames.peek() == Some(&frame) {
+            frames.next();
+            count += 1;
+        }
+        if count > 1 {
+            println!("    in {} ({} times)", frame, count);
+        } else {
+            println!("    in {}", frame);
+        }
+    }
+}
+
+/// Prints the files of an `ImportCycle` error, each file on its own line.
+fn print_import_cycle(files: &Vec<PathBuf>) {
+    println!("Import cycle detected:");
+    for (i, file) in files.iter().enumerate() {
+        if i == 0 {
+            println!("    {}", file.display());
+        } else {
+            println!("    imports {}", file.display());
+        }
+    }
+}
+
+/// Prints every location that was tried for a file that could not be found.
+fn print_not_file(tried: &Vec<String>) {
+    println!("File not found. Tried:");
+    for location in tried {
+        println!("    {}", location);
+    }
+}
+
 fn print_usage() {
-    eprintln!("Usage: foil [path/to/index.foil]");
+    eprintln!("Usage: foil [--max-depth N] [--stats] [-I DIR]... [path/to/index.foil]");
 }
 
 fn get_out_path() -> PathBuf {
@@ -71,12 +145,54 @@ fn to_src_root_path(s: &str) -> Option<PathBuf> {
     }
 }
 
-fn get_parameter() -> Option<String> {
-    let args: Vec<_> = env::args().collect();
-    if args.len() < 2 {
-        None
-    } else {
-        Some(args[1].to_string())
+/// The directories that imports are looked up in, the ones given on the command line first and
+/// then the ones in the `FOIL_PATH` environment variable.
+fn get_search_path(include_dirs: &Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut search_path = include_dirs.clone();
+    if let Some(foil_path) = env::var_os(FOIL_PATH) {
+        search_path.extend(env::split_paths(&foil_path).filter(|dir| dir != Path::new("")));
     }
+    search_path
 }
 
+fn get_options() -> Option<Options> {
+    let mut index_file = None;
+    let mut max_depth = DEFAULT_MAX_DEPTH;
+    let mut stats = false;
+    let mut include_dirs = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--max-depth" {
+            match args.next().and_then(|depth| depth.parse().ok()) {
+                Some(depth) => { max_depth = depth; },
+                None => { return None; },
+            }
+        } else if arg == "-I" || arg == "--include" {
+            let dir = match args.next() {
+                Some(dir) => dir,
+                None => { return None; },
+            };
+            match env::current_dir() {
+                Ok(current_dir) => { include_dirs.push(current_dir.join(dir)); },
+                Err(err) => {
+                    eprintln!("Could not resolve the include directory `{}`: {}", dir, err);
+                    return None;
+                },
+            }
+        } else if arg == "--stats" {
+            stats = true;
+        } else if index_file == None {
+            index_file = Some(arg);
+        } else {
+            return None;
+        }
+    }
+
+    index_file.map(|index_file| Options{
+        index_file: index_file,
+        max_depth: max_depth,
+        stats: stats,
+        include_dirs: include_dirs,
+    })
+}
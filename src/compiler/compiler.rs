@@ -1,27 +1,35 @@
 use std::io::{Read, Write};
 use std::fs::{self, File};
 use std::path::Path;
+use std::rc::Rc;
 
-use super::evaluator::{Evaluator, EvalResult, Scope, OpenScope};
+use super::evaluator::{Evaluator, EvalResult, Env, Context, StackFrame};
 use compiler::errors::EvalError;
 use super::tokenizer::Tokenizer;
 use super::parser::Parser;
 use super::models::Output;
+use super::data::{is_data_file, evaluate_data};
+use super::markdown::{is_markdown_file, evaluate_markdown};
+use super::front_matter::hide_front_matter;
 
-/// Evaluates a piece of foil-code.
+/// Evaluates a piece of foil-code as part of an existing build.
 ///
 /// # Arguments
 /// `text` - the foil code.
 /// `file_path` - the path to the file that contains the given foil code.
 /// `out_dir` - the output directory.
-pub fn evaluate_string(text: &str, file_path: &Path, out_dir: &Path) -> EvalResult {
+/// `context` - the state of the build.
+pub fn evaluate_string(text: &str, file_path: &Path, out_dir: &Path, context: Rc<Context>) -> EvalResult {
     let mut tokenizer = Tokenizer::new(&text);
     let mut parser = Parser::new(&mut tokenizer);
     if let Some(parse_res) = parser.next() {
         match parse_res {
             Ok(ast) => {
-                let scope = OpenScope::new();
-                Evaluator::new(&ast, Scope::Open(&scope), file_path.to_owned(), out_dir.to_owned()).eval()
+                Evaluator::with_context(&ast, 
+                                        Env::new(), 
+                                        Some(file_path.to_owned()), 
+                                        Some(out_dir.to_owned()), 
+                                        context).eval()
             },
             Err(err) => Err(EvalError::Parser(err)),
         }
@@ -30,41 +38,100 @@ pub fn evaluate_string(text: &str, file_path: &Path, out_dir: &Path) -> EvalResu
     }
 }
 
-/// Reads the file `file_path` and evaluates it's contents.
+/// Reads the file `file_path` and evaluates it's contents as a build of its own.
 /// Returns `EvalError::NotFile` if the file could not be opened.
 pub fn evaluate_file(file_path: &Path, out_dir: &Path) -> EvalResult  {
+    let context = Rc::new(Context::default());
+    let res = evaluate_file_with_context(file_path, out_dir, context.clone());
+    context.clear_modules();
+    res
+}
+
+/// Reads the file `file_path` and evaluates it's contents as part of an existing build.
+/// JSON, TOML and CSV files are converted into foil values and markdown files are rendered into
+/// a set of their front matter and html content instead of being evaluated. The front matter of
+/// foil files is ignored.
+/// Every file is only evaluated once per output directory and build, later calls return the
+/// cached output.
+/// Returns `EvalError::NotFile` if the file could not be opened, `EvalError::ImportCycle` if
+/// the file is already being evaluated, i.e. if it imports itself, and `EvalError::InvalidData`
+/// if a data file could not be parsed.
+pub fn evaluate_file_with_context(file_path: &Path, out_dir: &Path, context: Rc<Context>) -> EvalResult  {
     let mut f = match File::open(&file_path) {
         Ok(f) => f,
         Err(_err) => {
-            return Err(EvalError::NotFile(file_path.to_str().unwrap_or("None").to_string()));
+            return Err(EvalError::NotFile(vec![file_path.to_str().unwrap_or("None").to_string()]));
         }
     };
-    let mut contents = String::new();
 
+    let canonical_path = match file_path.canonicalize() {
+        Ok(path) => path,
+        Err(err) => { return Err(EvalError::IO(err)); },
+    };
+    if let Some(output) = context.cached_module(&canonical_path, &out_dir.to_path_buf()) {
+        return Ok(output);
+    }
+
+    let mut contents = String::new();
     let read_res = f.read_to_string(&mut contents);
     if let Err(err) = read_res {
         return Err(EvalError::IO(err));
     }
+    evaluate_contents_with_context(&contents, file_path, out_dir, context)
+}
 
-    evaluate_string(&contents, &file_path, &out_dir)
+/// Evaluates the contents of the file `file_path`, which have already been read, as part of an
+/// existing build. The file is evaluated and cached like in `evaluate_file_with_context`, but the
+/// cache is not looked up.
+pub fn evaluate_contents_with_context(contents: &str, file_path: &Path, out_dir: &Path, context: Rc<Context>) -> EvalResult  {
+    let canonical_path = match file_path.canonicalize() {
+        Ok(path) => path,
+        Err(err) => { return Err(EvalError::IO(err)); },
+    };
+    let _file = match context.enter_file(canonical_path.clone()) {
+        Ok(guard) => guard,
+        Err(err) => { return Err(err); },
+    };
+
+    let _frame = context.enter_frame(StackFrame::File(file_path.to_path_buf()));
+    let res = if is_data_file(file_path) {
+        evaluate_data(contents, file_path)
+    } else if is_markdown_file(file_path) {
+        evaluate_markdown(contents, file_path, out_dir, &context)
+    } else {
+        evaluate_string(&hide_front_matter(contents), &file_path, &out_dir, context.clone())
+    };
+    if let Ok(ref output) = res {
+        context.cache_module(canonical_path, out_dir.to_path_buf(), output.clone());
+    }
+    res
 }
 
+/// Evaluates the file `file_path` as a build of its own and writes the result to an html file in
+/// `out_dir`.
 pub fn build_file(file_path: &Path, out_dir: &Path) -> Result<(), EvalError>  {
-    let res = evaluate_file(file_path, out_dir);
+    let context = Rc::new(Context::default());
+    let res = build_file_with_context(file_path, out_dir, context.clone());
+    context.clear_modules();
+    res
+}
+
+/// Evaluates the file `file_path` and writes the result to an html file in `out_dir`.
+///
+/// # Arguments
+/// `file_path` - the file to build.
+/// `out_dir` - the output directory.
+/// `context` - the state of the build, e.g. the maximum evaluation depth.
+pub fn build_file_with_context(file_path: &Path, out_dir: &Path, context: Rc<Context>) -> Result<(), EvalError>  {
+    let res = evaluate_file_with_context(file_path, out_dir, context.clone())
+        .and_then(|output| output.to_string());
     match res {
         Ok(output) => {
-            match output.to_string() {
-                Ok(output) => {
-                    let mut out_index_file = out_dir.join(file_path.file_stem().unwrap());   
-                    out_index_file.set_extension("html");
-                    let outstr = format!("{}", output);
-                    write_to_file(&outstr, &out_index_file);
-                    Ok(())
-                },
-                Err(err) => {
-                    return Err(err);
-                }
-            }
+            let mut out_index_file = out_dir.join(file_path.file_stem().unwrap());   
+            out_index_file.set_extension("html");
+            let outstr = format!("{}", output);
+            write_to_file(&outstr, &out_index_file);
+            Ok(())
         },
         Err(err) => Err(err),
     }
@@ -0,0 +1,131 @@
+//! Conversion of structured data files into foil values.
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::ffi::OsStr;
+
+use serde_json;
+use toml;
+use csv;
+
+use compiler::evaluator::EvalResult;
+use compiler::errors::EvalError;
+use compiler::models::{Output, Thunk};
+
+/// Returns `true` if the file is a data file, i.e. if it is imported with `evaluate_data` instead
+/// of being evaluated as foil code.
+pub fn is_data_file(file_path: &Path) -> bool {
+    match file_path.extension().and_then(OsStr::to_str) {
+        Some("json") | Some("toml") | Some("csv") => true,
+        _ => false,
+    }
+}
+
+/// Converts the contents of a data file into a foil value. The format is chosen by the extension
+/// of the file.
+///
+/// JSON objects and TOML tables are converted to sets and arrays to lists. JSON `null` is
+/// converted to the empty list, so it is rendered as nothing and can't be told apart from `[]`.
+/// TOML datetimes are converted to strings. CSV files are converted to a list with a set of
+/// strings for every row.
+///
+/// # Arguments
+/// `text` - the contents of the file.
+/// `file_path` - the path to the file.
+///
+/// # Errors
+/// `EvalError::InvalidData` if the contents could not be parsed.
+pub fn evaluate_data(text: &str, file_path: &Path) -> EvalResult {
+    match file_path.extension().and_then(OsStr::to_str) {
+        Some("json") => evaluate_json(text, file_path),
+        Some("toml") => evaluate_toml(text, file_path),
+        _ => evaluate_csv(text, file_path),
+    }
+}
+
+fn evaluate_json(text: &str, file_path: &Path) -> EvalResult {
+    match serde_json::from_str(text) {
+        Ok(value) => Ok(from_json(value)),
+        Err(err) => Err(EvalError::InvalidData(file_path.to_path_buf(), Some(err.line()), err.to_string())),
+    }
+}
+
+fn from_json(value: serde_json::Value) -> Output {
+    match value {
+        serde_json::Value::Null => Output::List(Vec::new()),
+        serde_json::Value::Bool(b) => Output::Bool(b),
+        serde_json::Value::Number(n) => {
+            match n.as_i64() {
+                Some(i) => Output::Int(i),
+                None => Output::Double(n.as_f64().unwrap_or(0.0)),
+            }
+        },
+        serde_json::Value::String(s) => Output::String(s),
+        serde_json::Value::Array(items) => Output::List(items.into_iter().map(from_json).collect()),
+        serde_json::Value::Object(fields) => {
+            let fields = fields
+                .into_iter()
+                .map(|(name, value)| (name, Thunk::evaluated(from_json(value))))
+                .collect();
+            Output::Set(fields)
+        },
+    }
+}
+
+/// Converts a TOML document into a set. Also used for the front matter of content files.
+pub fn evaluate_toml(text: &str, file_path: &Path) -> EvalResult {
+    match text.parse::<toml::Value>() {
+        Ok(value) => Ok(from_toml(value)),
+        Err(err) => {
+            // The line reported by the toml parser starts at 0.
+            let line = err.line_col().map(|(line, _)| line + 1);
+            Err(EvalError::InvalidData(file_path.to_path_buf(), line, err.to_string()))
+        },
+    }
+}
+
+fn from_toml(value: toml::Value) -> Output {
+    match value {
+        toml::Value::String(s) => Output::String(s),
+        toml::Value::Integer(i) => Output::Int(i),
+        toml::Value::Float(f) => Output::Double(f),
+        toml::Value::Boolean(b) => Output::Bool(b),
+        toml::Value::Datetime(d) => Output::String(d.to_string()),
+        toml::Value::Array(items) => Output::List(items.into_iter().map(from_toml).collect()),
+        toml::Value::Table(fields) => {
+            let fields = fields
+                .into_iter()
+                .map(|(name, value)| (name, Thunk::evaluated(from_toml(value))))
+                .collect();
+            Output::Set(fields)
+        },
+    }
+}
+
+/// The first row holds the field names.
+fn evaluate_csv(text: &str, file_path: &Path) -> EvalResult {
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(err) => { return Err(csv_error(err, file_path)); },
+    };
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => { return Err(csv_error(err, file_path)); },
+        };
+        let fields: BTreeMap<String, Thunk> = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(name, value)| (name.to_string(), Thunk::evaluated(Output::String(value.to_string()))))
+            .collect();
+        rows.push(Output::Set(fields));
+    }
+    Ok(Output::List(rows))
+}
+
+fn csv_error(err: csv::Error, file_path: &Path) -> EvalError {
+    let line = err.position().map(|pos| pos.line() as usize);
+    EvalError::InvalidData(file_path.to_path_buf(), line, err.to_string())
+}
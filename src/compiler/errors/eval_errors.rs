@@ -14,9 +14,54 @@ pub enum EvalError {
     IO(IOError),
     IOUnknown,
     PathNotRelative(String),
-    NotFile(String),
+
+    /// A file could not be found. Holds every location that was tried.
+    NotFile(Vec<String>),
     OutputPathNotSpecified,
     NotStringable(Output),
+    FieldNotFound(Id),
+    NotASet(Output),
+    NotComparable(Output, Output),
+
+    /// A builtin function was called with an argument of the wrong type.
+    /// Holds the name of the function and the argument.
+    InvalidArgument(String, Output),
+    NotABool(Output),
+
+    /// Functions were compared for equality.
+    FnComparison(Output, Output),
+
+    /// An integer operation overflowed. Holds the operator and the operands.
+    ArithmeticOverflow(BinOp, Output, Output),
+
+    /// Integer division or modulo by zero. Holds the operator and the operands.
+    DivisionByZero(BinOp, Output, Output),
+
+    /// A function was called with more arguments than it takes.
+    /// Holds the position of the first argument too many and the value it was applied to.
+    TooManyArguments(usize, Output),
+
+    /// The evaluation was nested too deeply, most likely because of infinite recursion.
+    /// Holds the function calls and files that were being evaluated, from the outermost to the
+    /// innermost.
+    RecursionLimit(Vec<String>),
+
+    /// A value depends on itself, e.g. `let x = + x 1 in x`.
+    /// Holds the expression of the value.
+    ValueCycle(String),
+
+    /// A file imports itself, directly or through other files.
+    /// Holds the files of the cycle, starting and ending with the same file.
+    ImportCycle(Vec<PathBuf>),
+
+    /// An imported data file could not be parsed.
+    /// Holds the path of the file, the line of the error if it is known and a description of the
+    /// error.
+    InvalidData(PathBuf, Option<usize>, String),
+
+    /// A path literal is not a valid glob pattern.
+    /// Holds the pattern and a description of the error.
+    InvalidGlob(String, String),
 }
 impl PartialEq for EvalError {
     fn eq(&self, other: &EvalError) -> bool {
@@ -36,6 +81,28 @@ impl PartialEq for EvalError {
             (&EvalError::NotStringable(ref l),
              &EvalError::NotStringable(ref r)) => l == r,
             (&EvalError::OutputPathNotSpecified, &EvalError::OutputPathNotSpecified) => true,
+            (&EvalError::FieldNotFound(ref l), &EvalError::FieldNotFound(ref r)) => l == r,
+            (&EvalError::NotASet(ref l), &EvalError::NotASet(ref r)) => l == r,
+            (&EvalError::NotComparable(ref l1, ref l2),
+             &EvalError::NotComparable(ref r1, ref r2)) => l1 == r1 && l2 == r2,
+            (&EvalError::InvalidArgument(ref lname, ref l),
+             &EvalError::InvalidArgument(ref rname, ref r)) => lname == rname && l == r,
+            (&EvalError::NotABool(ref l), &EvalError::NotABool(ref r)) => l == r,
+            (&EvalError::FnComparison(ref l1, ref l2),
+             &EvalError::FnComparison(ref r1, ref r2)) => l1 == r1 && l2 == r2,
+            (&EvalError::ArithmeticOverflow(ref lop, ref lo1, ref lo2),
+             &EvalError::ArithmeticOverflow(ref rop, ref ro1, ref ro2)) => lop == rop && lo1 == ro1 && lo2 == ro2,
+            (&EvalError::DivisionByZero(ref lop, ref lo1, ref lo2),
+             &EvalError::DivisionByZero(ref rop, ref ro1, ref ro2)) => lop == rop && lo1 == ro1 && lo2 == ro2,
+            (&EvalError::TooManyArguments(ref lpos, ref l),
+             &EvalError::TooManyArguments(ref rpos, ref r)) => lpos == rpos && l == r,
+            (&EvalError::RecursionLimit(ref l), &EvalError::RecursionLimit(ref r)) => l == r,
+            (&EvalError::ValueCycle(ref l), &EvalError::ValueCycle(ref r)) => l == r,
+            (&EvalError::ImportCycle(ref l), &EvalError::ImportCycle(ref r)) => l == r,
+            (&EvalError::InvalidData(ref lpath, ref lline, ref l),
+             &EvalError::InvalidData(ref rpath, ref rline, ref r)) => lpath == rpath && lline == rline && l == r,
+            (&EvalError::InvalidGlob(ref lpattern, ref l),
+             &EvalError::InvalidGlob(ref rpattern, ref r)) => lpattern == rpattern && l == r,
             (_, _) => false,
         }
     }
@@ -60,6 +127,22 @@ impl Clone for EvalError {
             &EvalError::NotFile(ref x) => EvalError::NotFile(x.clone()),
             &EvalError::NotStringable(ref x) => EvalError::NotStringable(x.clone()),
             &EvalError::OutputPathNotSpecified => EvalError::OutputPathNotSpecified,
+            &EvalError::FieldNotFound(ref x) => EvalError::FieldNotFound(x.clone()),
+            &EvalError::NotASet(ref x) => EvalError::NotASet(x.clone()),
+            &EvalError::NotComparable(ref x, ref y) => EvalError::NotComparable(x.clone(), y.clone()),
+            &EvalError::InvalidArgument(ref x, ref y) => EvalError::InvalidArgument(x.clone(), y.clone()),
+            &EvalError::NotABool(ref x) => EvalError::NotABool(x.clone()),
+            &EvalError::FnComparison(ref x, ref y) => EvalError::FnComparison(x.clone(), y.clone()),
+            &EvalError::ArithmeticOverflow(ref x, ref y, ref z) => 
+                EvalError::ArithmeticOverflow(x.clone(), y.clone(), z.clone()),
+            &EvalError::DivisionByZero(ref x, ref y, ref z) => 
+                EvalError::DivisionByZero(x.clone(), y.clone(), z.clone()),
+            &EvalError::TooManyArguments(ref x, ref y) => EvalError::TooManyArguments(*x, y.clone()),
+            &EvalError::RecursionLimit(ref x) => EvalError::RecursionLimit(x.clone()),
+            &EvalError::ValueCycle(ref x) => EvalError::ValueCycle(x.clone()),
+            &EvalError::ImportCycle(ref x) => EvalError::ImportCycle(x.clone()),
+            &EvalError::InvalidData(ref x, ref y, ref z) => EvalError::InvalidData(x.clone(), *y, z.clone()),
+            &EvalError::InvalidGlob(ref x, ref y) => EvalError::InvalidGlob(x.clone(), y.clone()),
         }
     }
 }
@@ -19,6 +19,13 @@ pub enum ParseError {
     ExpectedColon(Token),
     ExpectedGroupR(Token),
     ExpectedString(Token),
+
+    /// The same name is bound more than once in a `let`.
+    /// Holds the name and the positions of the first and the duplicate binding.
+    DuplicateBinding(String, usize, usize),
+
+    /// A function call without arguments. Holds the position of the closing parenthesis.
+    ExpectedArgument(usize),
 }
 
 
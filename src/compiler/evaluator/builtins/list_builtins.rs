@@ -0,0 +1,160 @@
+//! Builtin functions for working with lists.
+//! All functions are called with exactly as many arguments as their arity in `get_builtin`.
+use std::cmp::Ordering;
+use compiler::models::Output;
+use compiler::evaluator::EvalResult;
+use compiler::errors::EvalError;
+use super::{call, expect_list, expect_int, expect_bool};
+
+/// The maximum number of elements of a list created with `range`.
+const MAX_RANGE_LENGTH: i64 = 1000000;
+
+/// `(map f xs)` - applies `f` to every element of `xs`.
+pub fn map(args: Vec<Output>) -> EvalResult {
+    let mut args = args.into_iter();
+    let func = args.next().unwrap();
+    let items = expect_list("map", args.next().unwrap())?;
+
+    items.into_iter()
+        .map(|item| { call(&func, item) })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Output::List)
+}
+
+/// `(filter f xs)` - the elements of `xs` for which `f` returns `true`.
+pub fn filter(args: Vec<Output>) -> EvalResult {
+    let mut args = args.into_iter();
+    let func = args.next().unwrap();
+    let items = expect_list("filter", args.next().unwrap())?;
+
+    let mut filtered = vec![];
+    for item in items.into_iter() {
+        let keep = call(&func, item.clone()).and_then(|keep| { expect_bool("filter", keep) })?;
+        if keep {
+            filtered.push(item);
+        }
+    }
+    Ok(Output::List(filtered))
+}
+
+/// `(foldl f init xs)` - combines the elements of `xs` from the left, i.e. 
+/// `(f (f (f init x1) x2) x3)`.
+pub fn foldl(args: Vec<Output>) -> EvalResult {
+    let mut args = args.into_iter();
+    let func = args.next().unwrap();
+    let init = args.next().unwrap();
+    let items = expect_list("foldl", args.next().unwrap())?;
+
+    items.into_iter()
+        .fold(Ok(init), |acc, item| {
+            acc.and_then(|acc| { call(&func, acc) })
+               .and_then(|func| { call(&func, item) })
+        })
+}
+
+/// `(sort_by f xs)` - sorts `xs` by the keys returned by `f`. The sort is stable.
+pub fn sort_by(args: Vec<Output>) -> EvalResult {
+    let mut args = args.into_iter();
+    let func = args.next().unwrap();
+    let items = expect_list("sort_by", args.next().unwrap())?;
+
+    let mut keyed = vec![];
+    for item in items.into_iter() {
+        let key = call(&func, item.clone())?;
+        keyed.push((key, item));
+    }
+    sort_keyed(keyed).map(Output::List)
+}
+
+/// Sorts the items by their keys and returns the items. The sort is stable.
+///
+/// # Errors
+/// `EvalError::NotComparable` if two keys can't be compared.
+pub fn sort_keyed(mut keyed: Vec<(Output, Output)>) -> Result<Vec<Output>, EvalError> {
+    // `sort_by` can't return errors so remember the first failed comparison.
+    let mut error = None;
+    keyed.sort_by(|&(ref l, _), &(ref r, _)| {
+        match l.compare(r) {
+            Ok(ordering) => ordering,
+            Err(err) => {
+                if error.is_none() {
+                    error = Some(err);
+                }
+                Ordering::Equal
+            }
+        }
+    });
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(keyed.into_iter().map(|(_, item)| item).collect()),
+    }
+}
+
+/// `(length xs)` - the number of elements in `xs`, or the number of characters if `xs` is a
+/// string.
+pub fn length(args: Vec<Output>) -> EvalResult {
+    match args.into_iter().next().unwrap() {
+        Output::List(items) => Ok(Output::Int(items.len() as i64)),
+        Output::String(s) => Ok(Output::Int(s.chars().count() as i64)),
+        other => Err(EvalError::InvalidArgument("length".to_string(), other)),
+    }
+}
+
+/// `(reverse xs)` - the elements of `xs` in reverse order.
+pub fn reverse(args: Vec<Output>) -> EvalResult {
+    let mut items = expect_list("reverse", args.into_iter().next().unwrap())?;
+    items.reverse();
+    Ok(Output::List(items))
+}
+
+/// `(concat xss)` - joins a list of lists into one list.
+pub fn concat(args: Vec<Output>) -> EvalResult {
+    let lists = expect_list("concat", args.into_iter().next().unwrap())?;
+
+    let mut joined = vec![];
+    for list in lists.into_iter() {
+        joined.extend(expect_list("concat", list)?);
+    }
+    Ok(Output::List(joined))
+}
+
+/// `(range from to)` - the integers from `from` up to, but not including, `to`.
+/// Returns `EvalError::InvalidArgument` for `to` if the range would have more than
+/// `MAX_RANGE_LENGTH` elements.
+pub fn range(args: Vec<Output>) -> EvalResult {
+    let mut args = args.into_iter();
+    let from = expect_int("range", args.next().unwrap())?;
+    let to = expect_int("range", args.next().unwrap())?;
+
+    match to.checked_sub(from) {
+        Some(len) if len <= MAX_RANGE_LENGTH => Ok(Output::List((from..to).map(Output::Int).collect())),
+        _ => Err(EvalError::InvalidArgument("range".to_string(), Output::Int(to))),
+    }
+}
+
+/// `(take n xs)` - the first `n` elements of `xs`.
+pub fn take(args: Vec<Output>) -> EvalResult {
+    let mut args = args.into_iter();
+    let n = expect_count("take", args.next().unwrap())?;
+    let items = expect_list("take", args.next().unwrap())?;
+
+    Ok(Output::List(items.into_iter().take(n).collect()))
+}
+
+/// `(drop n xs)` - all but the first `n` elements of `xs`.
+pub fn drop(args: Vec<Output>) -> EvalResult {
+    let mut args = args.into_iter();
+    let n = expect_count("drop", args.next().unwrap())?;
+    let items = expect_list("drop", args.next().unwrap())?;
+
+    Ok(Output::List(items.into_iter().skip(n).collect()))
+}
+
+/// Expects a non-negative integer.
+fn expect_count(func_name: &str, value: Output) -> Result<usize, EvalError> {
+    match value {
+        Output::Int(x) if x >= 0 => Ok(x as usize),
+        other => Err(EvalError::InvalidArgument(func_name.to_string(), other)),
+    }
+}
@@ -0,0 +1,56 @@
+use compiler::models::{Builtin, Output};
+use compiler::evaluator::EvalResult;
+use compiler::errors::EvalError;
+
+mod list_builtins;
+pub use self::list_builtins::sort_keyed;
+
+#[cfg(test)] mod tests;
+
+/// Returns the builtin function with the name `name`, or `None` if there is no such builtin.
+pub fn get_builtin(name: &str) -> Option<Output> {
+    let builtin = match name {
+        "map" => Builtin::new("map", 2, list_builtins::map),
+        "filter" => Builtin::new("filter", 2, list_builtins::filter),
+        "foldl" => Builtin::new("foldl", 3, list_builtins::foldl),
+        "sort_by" => Builtin::new("sort_by", 2, list_builtins::sort_by),
+        "length" => Builtin::new("length", 1, list_builtins::length),
+        "reverse" => Builtin::new("reverse", 1, list_builtins::reverse),
+        "concat" => Builtin::new("concat", 1, list_builtins::concat),
+        "range" => Builtin::new("range", 2, list_builtins::range),
+        "take" => Builtin::new("take", 2, list_builtins::take),
+        "drop" => Builtin::new("drop", 2, list_builtins::drop),
+        _ => { return None; },
+    };
+    Some(Output::Builtin(builtin))
+}
+
+/// Calls the function `func` with the argument `arg`.
+fn call(func: &Output, arg: Output) -> EvalResult {
+    match func {
+        &Output::Fn(ref func) => func.apply(arg),
+        &Output::Builtin(ref func) => func.apply(arg),
+        not_func => Err(EvalError::NotAFunction(not_func.clone())),
+    }
+}
+
+fn expect_list(func_name: &str, value: Output) -> Result<Vec<Output>, EvalError> {
+    match value {
+        Output::List(items) => Ok(items),
+        other => Err(EvalError::InvalidArgument(func_name.to_string(), other)),
+    }
+}
+
+fn expect_int(func_name: &str, value: Output) -> Result<i64, EvalError> {
+    match value {
+        Output::Int(x) => Ok(x),
+        other => Err(EvalError::InvalidArgument(func_name.to_string(), other)),
+    }
+}
+
+fn expect_bool(func_name: &str, value: Output) -> Result<bool, EvalError> {
+    match value {
+        Output::Bool(x) => Ok(x),
+        other => Err(EvalError::InvalidArgument(func_name.to_string(), other)),
+    }
+}
@@ -0,0 +1,82 @@
+use compiler::tokenizer::Tokenizer;
+use compiler::parser::Parser;
+use compiler::evaluator::{Evaluator, EvalResult, Env};
+use compiler::models::Output;
+use compiler::errors::EvalError;
+
+fn eval_str(input: &str) -> EvalResult {
+    let mut tokenizer = Tokenizer::new(input);
+    let mut parser = Parser::new(&mut tokenizer);
+    let ast = parser.next().unwrap().unwrap();
+
+    Evaluator::without_files(&ast, Env::new()).eval()
+}
+
+fn ints(values: Vec<i64>) -> EvalResult {
+    Ok(Output::List(values.into_iter().map(Output::Int).collect()))
+}
+
+#[test]
+fn map_works() {
+    assert_eq!(ints(vec![2, 3, 4]), eval_str("((map fn x: + x 1) [1 2 3])"));
+}
+
+#[test]
+fn filter_works() {
+    assert_eq!(ints(vec![1, 3]), eval_str("((filter fn x: == % x 2 1) [1 2 3 4])"));
+}
+
+#[test]
+fn foldl_works() {
+    assert_eq!(Ok(Output::Int(6)), eval_str("(((foldl fn acc: fn x: + acc x) 0) [1 2 3])"));
+}
+
+#[test]
+fn sort_by_works() {
+    let input = "((sort_by fn p: p.date) [set { date = 3 } set { date = 1 } set { date = 2 }])";
+    assert_eq!(ints(vec![1, 2, 3]), eval_str(&format!("((map fn p: p.date) {})", input)));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn sort_by_should_return_NotComparable_for_mixed_keys() {
+    match eval_str("((sort_by fn x: x) [\"a\" 1])") {
+        Err(EvalError::NotComparable(..)) => {},
+        other => panic!("Expected NotComparable but got {:?}", other),
+    }
+}
+
+#[test]
+fn length_reverse_and_concat_work() {
+    assert_eq!(Ok(Output::Int(3)), eval_str("(length [1 2 3])"));
+    assert_eq!(ints(vec![3, 2, 1]), eval_str("(reverse [1 2 3])"));
+    assert_eq!(ints(vec![1, 2, 3]), eval_str("(concat [[1] [] [2 3]])"));
+}
+
+#[test]
+fn range_take_and_drop_work() {
+    assert_eq!(ints(vec![0, 1, 2, 3]), eval_str("((range 0) 4)"));
+    assert_eq!(ints(vec![0, 1]), eval_str("((take 2) ((range 0) 4))"));
+    assert_eq!(ints(vec![2, 3]), eval_str("((drop 2) ((range 0) 4))"));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn range_should_return_InvalidArgument_for_too_long_ranges() {
+    let expected = Err(EvalError::InvalidArgument("range".to_string(), Output::Int(1000000000)));
+    assert_eq!(expected, eval_str("((range 0) 1000000000)"));
+    let expected = Err(EvalError::InvalidArgument("range".to_string(), Output::Int(9223372036854775807)));
+    assert_eq!(expected, eval_str("((range - 0 1) 9223372036854775807)"));
+}
+
+#[test]
+fn builtins_can_be_shadowed() {
+    assert_eq!(Ok(Output::Int(1)), eval_str("let length = fn x: 1 in (length [1 2 3])"));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn should_return_InvalidArgument_for_non_lists() {
+    let expected = Err(EvalError::InvalidArgument("reverse".to_string(), Output::Int(1)));
+    assert_eq!(expected, eval_str("(reverse 1)"));
+}
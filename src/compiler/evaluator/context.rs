@@ -0,0 +1,238 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use compiler::errors::EvalError;
+use compiler::models::{Ast, Output};
+
+/// The default maximum evaluation depth.
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+
+/// A function call or a file that is currently being evaluated.
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum StackFrame {
+    /// The call of a function with the argument at the position.
+    Call(Rc<Ast>, usize),
+
+    /// The evaluation of a file.
+    File(PathBuf),
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &StackFrame::Call(ref func, pos) => write!(f, "{} at {}", func, pos),
+            &StackFrame::File(ref path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// State that is shared between all evaluators of one build.
+pub struct Context {
+    /// The maximum number of nested evaluations.
+    pub max_depth: usize,
+
+    /// The directory that root-relative imports (`import "@/lib/nav.foil"`) are resolved against.
+    /// Defaults to the current directory.
+    pub root: Option<PathBuf>,
+
+    /// The library directories that imports are looked up in when they are not found relative to
+    /// the importing file.
+    pub search_path: Vec<PathBuf>,
+
+    /// The current number of nested evaluations.
+    depth: Cell<usize>,
+
+    /// The function calls and files that are currently being evaluated, from the outermost to
+    /// the innermost.
+    frames: RefCell<Vec<StackFrame>>,
+
+    /// The canonical paths of the files that are currently being evaluated, from the outermost
+    /// to the innermost.
+    files: RefCell<Vec<PathBuf>>,
+
+    /// The outputs of the files that have already been evaluated, keyed by the canonical path of
+    /// the file and the output directory.
+    modules: RefCell<HashMap<(PathBuf, PathBuf), Output>>,
+
+    /// The number of module lookups that were found in the cache.
+    cache_hits: Cell<usize>,
+
+    /// The number of module lookups that were not found in the cache.
+    cache_misses: Cell<usize>,
+}
+
+impl Context {
+    pub fn new(max_depth: usize) -> Self {
+        Context{
+            max_depth: max_depth, 
+            root: None,
+            search_path: Vec::new(),
+            depth: Cell::new(0), 
+            frames: RefCell::new(Vec::new()),
+            files: RefCell::new(Vec::new()),
+            modules: RefCell::new(HashMap::new()),
+            cache_hits: Cell::new(0),
+            cache_misses: Cell::new(0),
+        }
+    }
+
+    /// Enters a nested evaluation. The evaluation is left when the returned guard is dropped.
+    ///
+    /// # Errors
+    /// `EvalError::RecursionLimit` if the maximum depth has been reached.
+    pub fn enter<'c>(&'c self) -> Result<DepthGuard<'c>, EvalError> {
+        if self.depth.get() >= self.max_depth {
+            // The frames are only described when the limit is reached, as this happens at most once
+            // per build while frames are entered on every call.
+            let frames = self.frames.borrow().iter().map(StackFrame::to_string).collect();
+            return Err(EvalError::RecursionLimit(frames));
+        }
+        self.depth.set(self.depth.get() + 1);
+        Ok(DepthGuard{context: self})
+    }
+
+    /// Enters a function call or a file. The frame is left when the returned guard is dropped.
+    pub fn enter_frame<'c>(&'c self, frame: StackFrame) -> FrameGuard<'c> {
+        self.frames.borrow_mut().push(frame);
+        FrameGuard{context: self}
+    }
+
+    /// Enters the evaluation of a file. The file is left when the returned guard is dropped.
+    ///
+    /// # Arguments
+    /// `file` - the canonical path of the file.
+    ///
+    /// # Errors
+    /// `EvalError::ImportCycle` if the file is already being evaluated.
+    pub fn enter_file<'c>(&'c self, file: PathBuf) -> Result<FileGuard<'c>, EvalError> {
+        let start = self.files.borrow().iter().position(|active| active == &file);
+        if let Some(start) = start {
+            let mut cycle = self.files.borrow()[start..].to_vec();
+            cycle.push(file);
+            return Err(EvalError::ImportCycle(cycle));
+        }
+        self.files.borrow_mut().push(file);
+        Ok(FileGuard{context: self})
+    }
+
+    /// Returns `true` if the file is currently being evaluated.
+    ///
+    /// # Arguments
+    /// `file` - the canonical path of the file.
+    pub fn is_evaluating(&self, file: &Path) -> bool {
+        self.files.borrow().iter().any(|active| active == file)
+    }
+
+    /// Looks up the output of a file that has already been evaluated in this build and counts
+    /// the lookup as a cache hit or miss.
+    ///
+    /// # Arguments
+    /// `file` - the canonical path of the file.
+    /// `out_dir` - the output directory the file was evaluated for.
+    pub fn cached_module(&self, file: &PathBuf, out_dir: &PathBuf) -> Option<Output> {
+        let key = (file.clone(), out_dir.clone());
+        let cached = self.modules.borrow().get(&key).cloned();
+        match cached {
+            Some(_) => self.cache_hits.set(self.cache_hits.get() + 1),
+            None => self.cache_misses.set(self.cache_misses.get() + 1),
+        }
+        cached
+    }
+
+    /// Stores the output of an evaluated file so that later imports can reuse it.
+    pub fn cache_module(&self, file: PathBuf, out_dir: PathBuf, output: Output) {
+        self.modules.borrow_mut().insert((file, out_dir), output);
+    }
+
+    /// Clears the module cache. The cached outputs can refer back to the context, e.g. functions
+    /// that are defined in the cached files, so the context is only freed once the cache has
+    /// been cleared. Outputs of the build can still be used afterwards.
+    pub fn clear_modules(&self) {
+        // The outputs are dropped after the borrow has ended, since dropping them might drop
+        // other contexts.
+        let modules = mem::replace(&mut *self.modules.borrow_mut(), HashMap::new());
+        drop(modules);
+    }
+
+    /// The number of module lookups that were found in the cache.
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits.get()
+    }
+
+    /// The number of module lookups that were not found in the cache.
+    pub fn cache_misses(&self) -> usize {
+        self.cache_misses.get()
+    }
+}
+
+// The cached outputs may contain closures which refer back to the context, so the module cache
+// is left out of comparisons and debug output.
+impl PartialEq for Context {
+    fn eq(&self, other: &Context) -> bool {
+        self.max_depth == other.max_depth &&
+            self.root == other.root &&
+            self.search_path == other.search_path &&
+            self.depth == other.depth &&
+            self.frames == other.frames &&
+            self.files == other.files
+    }
+}
+
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut modules: Vec<PathBuf> = self.modules.borrow().keys()
+            .map(|&(ref file, _)| file.clone())
+            .collect();
+        modules.sort();
+        f.debug_struct("Context")
+            .field("max_depth", &self.max_depth)
+            .field("root", &self.root)
+            .field("search_path", &self.search_path)
+            .field("depth", &self.depth.get())
+            .field("frames", &self.frames.borrow())
+            .field("files", &self.files.borrow())
+            .field("modules", &modules)
+            .finish()
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context::new(DEFAULT_MAX_DEPTH)
+    }
+}
+
+/// Decreases the depth of the context when it is dropped.
+pub struct DepthGuard<'c> {
+    context: &'c Context,
+}
+impl<'c> Drop for DepthGuard<'c> {
+    fn drop(&mut self) {
+        self.context.depth.set(self.context.depth.get() - 1);
+    }
+}
+
+/// Removes the innermost frame of the context when it is dropped.
+pub struct FrameGuard<'c> {
+    context: &'c Context,
+}
+impl<'c> Drop for FrameGuard<'c> {
+    fn drop(&mut self) {
+        self.context.frames.borrow_mut().pop();
+    }
+}
+
+/// Removes the innermost file of the context when it is dropped.
+pub struct FileGuard<'c> {
+    context: &'c Context,
+}
+impl<'c> Drop for FileGuard<'c> {
+    fn drop(&mut self) {
+        self.context.files.borrow_mut().pop();
+    }
+}
@@ -0,0 +1,245 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter, self};
+use std::path::PathBuf;
+use std::rc::{Rc, Weak};
+use compiler::models::{Ast, Thunk};
+use super::context::Context;
+use super::evaluator::EvalResult;
+use super::builtins::get_builtin;
+
+/// An environment of variable definitions.
+///
+/// An environment is a chain of frames, from the innermost scope up to the root scope. The chain
+/// is reference counted, so cloning an `Env` is cheap and the same variable definitions are
+/// shared between let scopes, closures and sets.
+#[derive(Clone)]
+pub struct Env {
+    frame: Option<Rc<Frame>>,
+}
+
+/// The variable definitions of one scope.
+struct Frame {
+    vars: Vars,
+    parent: Env,
+}
+
+enum Vars {
+    /// Definitions that are evaluated in the environment they were defined in.
+    Values(Rc<HashMap<String, Thunk>>),
+
+    /// Definitions that refer to themselves or to each other.
+    Recursive(Rc<RecursiveVars>),
+}
+
+/// Variable definitions that refer to themselves or to each other, e.g. a recursive function.
+///
+/// The values of such definitions usually refer back to the environment they are evaluated in,
+/// e.g. a function that calls itself holds on to its own definition. The frame therefore only
+/// keeps the expressions alive, the thunks are held by the scope of the definitions and are
+/// created again when the frame is used after the scope is gone.
+struct RecursiveVars {
+    exprs: HashMap<String, Rc<Ast>>,
+    parent: Env,
+    file_path: Option<PathBuf>,
+    out_path: Option<PathBuf>,
+    context: Rc<Context>,
+    thunks: RefCell<Weak<HashMap<String, Thunk>>>,
+}
+
+impl Env {
+    /// Creates a new root environment with no variable definitions.
+    pub fn new() -> Self {
+        Env{frame: None}
+    }
+
+    /// Creates a child environment with the given variable definitions.
+    pub fn with_vars(&self, vars: HashMap<String, Thunk>) -> Env {
+        self.child(Vars::Values(Rc::new(vars)))
+    }
+
+    /// Creates a child environment with variable definitions that can refer to themselves and to
+    /// each other. The definitions are evaluated in an environment that doesn't keep their
+    /// values alive, so the values can refer back to it without creating reference cycles.
+    ///
+    /// # Arguments
+    /// `exprs` - the expressions of the variables.
+    /// `file_path` - the file in which the variables are defined.
+    /// `out_path` - the output directory of the file.
+    /// `context` - the state of the build.
+    pub fn with_recursive_vars(&self,
+                               exprs: HashMap<String, Rc<Ast>>,
+                               file_path: Option<PathBuf>,
+                               out_path: Option<PathBuf>,
+                               context: Rc<Context>) -> Env {
+        let vars = RecursiveVars{
+            exprs: exprs,
+            parent: self.clone(),
+            file_path: file_path,
+            out_path: out_path,
+            context: context,
+            thunks: RefCell::new(Weak::new()),
+        };
+        self.child(Vars::Values(RecursiveVars::thunks(&Rc::new(vars))))
+    }
+
+    /// Creates an environment with only the given variables of this environment, e.g. the free
+    /// variables of a function. Nothing is evaluated, the variables share their definitions with
+    /// this environment. Names that are not defined are left out, so they are looked up among
+    /// the builtin functions.
+    pub fn capture(&self, names: &HashSet<String>) -> Env {
+        let mut remaining: Vec<&str> = names.iter().map(String::as_str).collect();
+        let mut vars = HashMap::new();
+        let mut recursive = Vec::new();
+        let mut env = self;
+        while let Some(ref frame) = env.frame {
+            if remaining.is_empty() {
+                break;
+            }
+            match frame.vars {
+                Vars::Values(ref values) => {
+                    remaining.retain(|name| match values.get(*name) {
+                        Some(thunk) => {
+                            vars.insert(name.to_string(), thunk.clone());
+                            false
+                        },
+                        None => true,
+                    });
+                },
+                Vars::Recursive(ref defs) => {
+                    // The definitions are kept as they are, copying their thunks would keep
+                    // them alive.
+                    let len = remaining.len();
+                    remaining.retain(|name| !defs.exprs.contains_key(*name));
+                    if remaining.len() < len {
+                        recursive.push(defs.clone());
+                    }
+                },
+            }
+            env = &frame.parent;
+        }
+
+        // The recursive definitions keep their order, so inner definitions still shadow outer
+        // ones.
+        let mut captured = Env::new();
+        for defs in recursive.into_iter().rev() {
+            captured = captured.child(Vars::Recursive(defs));
+        }
+        if vars.is_empty() {
+            captured
+        } else {
+            captured.with_vars(vars)
+        }
+    }
+
+    fn child(&self, vars: Vars) -> Env {
+        Env{frame: Some(Rc::new(Frame{vars: vars, parent: self.clone()}))}
+    }
+
+    /// Looks up a variable definition and evaluates it.
+    /// Every variable definition is evaluated at most once, after that the result is reused.
+    ///
+    /// # Attributes
+    /// `id_name` - the name of the variable to look up.
+    ///
+    /// If there is no variable with the name `id_name` in any frame of the environment then the
+    /// builtin functions are searched. If there is no builtin with that name either then `None`
+    /// is returned.
+    pub fn get_value(&self, id_name: &str) -> Option<EvalResult> {
+        let mut env = self;
+        while let Some(ref frame) = env.frame {
+            match frame.vars {
+                Vars::Values(ref vars) => {
+                    if let Some(thunk) = vars.get(id_name) {
+                        return Some(thunk.eval());
+                    }
+                },
+                Vars::Recursive(ref vars) if vars.exprs.contains_key(id_name) => {
+                    // The thunks are kept alive during the evaluation, so that the definitions
+                    // it refers to are only evaluated once.
+                    let thunks = RecursiveVars::thunks(vars);
+                    return Some(thunks[id_name].eval());
+                },
+                Vars::Recursive(_) => {},
+            }
+            env = &frame.parent;
+        }
+        get_builtin(id_name).map(Ok)
+    }
+
+    /// Looks up the thunk of a variable without evaluating it.
+    ///
+    /// Returns `None` if the variable is not defined in the environment, or if it is one of the
+    /// recursive definitions, whose thunks are created again when they are not alive anymore.
+    pub fn get_thunk(&self, id_name: &str) -> Option<Thunk> {
+        let mut env = self;
+        while let Some(ref frame) = env.frame {
+            match frame.vars {
+                Vars::Values(ref vars) => {
+                    if let Some(thunk) = vars.get(id_name) {
+                        return Some(thunk.clone());
+                    }
+                },
+                Vars::Recursive(ref vars) if vars.exprs.contains_key(id_name) => {
+                    return None;
+                },
+                Vars::Recursive(_) => {},
+            }
+            env = &frame.parent;
+        }
+        None
+    }
+}
+
+impl RecursiveVars {
+    /// Returns the thunks of the definitions, or creates them if they are not alive anymore.
+    fn thunks(vars: &Rc<RecursiveVars>) -> Rc<HashMap<String, Thunk>> {
+        if let Some(thunks) = vars.thunks.borrow().upgrade() {
+            return thunks;
+        }
+        let env = vars.parent.child(Vars::Recursive(vars.clone()));
+        let thunks: HashMap<String, Thunk> = vars.exprs.iter()
+            .map(|(name, expr)| {
+                let thunk = Thunk::new(expr.clone(), 
+                                       env.clone(), 
+                                       vars.file_path.clone(), 
+                                       vars.out_path.clone(), 
+                                       vars.context.clone());
+                (name.clone(), thunk)
+            })
+            .collect();
+        let thunks = Rc::new(thunks);
+        *vars.thunks.borrow_mut() = Rc::downgrade(&thunks);
+        thunks
+    }
+}
+
+impl PartialEq for Env {
+    /// Environments are equal if they are the same environment.
+    fn eq(&self, other: &Env) -> bool {
+        match (&self.frame, &other.frame) {
+            (&Some(ref l), &Some(ref r)) => Rc::ptr_eq(l, r),
+            (&None, &None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Debug for Env {
+    /// Only the names of the variables are printed since the values might refer to the
+    /// environment itself.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut frames = f.debug_list();
+        let mut env = self;
+        while let Some(ref frame) = env.frame {
+            let mut names: Vec<&String> = match frame.vars {
+                Vars::Values(ref vars) => vars.keys().collect(),
+                Vars::Recursive(ref vars) => vars.exprs.keys().collect(),
+            };
+            names.sort();
+            frames.entry(&names);
+            env = &frame.parent;
+        }
+        frames.finish()
+    }
+}
@@ -1,76 +1,148 @@
 use std::path::{PathBuf, Path};
-use compiler::models::{Ast, Output};
+use std::rc::Rc;
+use stacker;
+use compiler::models::{Ast, Output, Thunk, Closure};
 
-use super::scope::Scope;
+use super::env::Env;
+use super::context::{Context, StackFrame};
 use compiler::errors::EvalError;
 
 use super::evaluators::{
     evaluate_binop, 
+    evaluate_unaryop,
     evaluate_html, 
     evaluate_html_closed,
     evaluate_import,
+    evaluate_collection,
     evaluate_closure,
     evaluate_call,
     evaluate_id,
     evaluate_val,
     evaluate_let,
+    evaluate_set,
+    evaluate_field,
+    evaluate_list,
+    evaluate_if,
 };
 
 pub type EvalResult = Result<Output, EvalError>;
 
+/// The stack space that has to be left before an evaluation level starts. Evaluation is
+/// recursive, so a new stack segment is allocated when less is left.
+const RED_ZONE: usize = 128 * 1024;
+
+/// The size of the stack segments that are allocated when the stack runs out.
+const STACK_SEGMENT: usize = 2 * 1024 * 1024;
+
+/// The result of evaluating an expression up to the first call in tail position.
+pub enum Step {
+    /// The expression has been evaluated completely.
+    Done(EvalResult),
+
+    /// The value of the expression is the result of calling the closure with the argument.
+    /// Also holds the frame of the call.
+    Call(Closure, Thunk, StackFrame),
+}
+
 /// A struct that holds all the relevant information to evaluate an AST (Abstract Syntax Tree)
 #[derive(PartialEq)]
 #[derive(Debug)]
-pub struct Evaluator<'scope, 'ast: 'scope> {
-    /// Scope of the evaluation. Used for looking up function and variable references.
-    pub scope: Scope<'scope, 'ast>,
+pub struct Evaluator<'ast> {
+    /// Environment of the evaluation. Used for looking up function and variable references.
+    pub env: Env,
 
     /// The directory to which processed files are written.
     /// If it is `None` then evaluation of paths will return `EvalError::OutputPathNotSpecified`.
     pub out_path: Option<PathBuf>,
 
+    /// The path to the file that contains the expression.
+    /// Paths and imports are resolved relative to the directory of this file.
+    pub file_path: Option<PathBuf>,
+
+    /// State that is shared between all evaluators of the build.
+    pub context: Rc<Context>,
+
     expr: &'ast Ast,
-    file_path: Option<PathBuf>,
 }
-impl<'scope, 'ast: 'scope> Evaluator<'scope, 'ast> {
+impl<'ast> Evaluator<'ast> {
     /// # Arguments
     /// `expr` - The AST (Abstract Syntax Tree) to be evaluated.
-    /// `scope` - The scope of the evaluation.
+    /// `env` - The environment of the evaluation.
     /// `file_path` - The path to the file for which the AST has been evaluated.
-    pub fn new(expr: &'ast Ast, scope: Scope<'scope, 'ast>, file_path: PathBuf, out_path: PathBuf) -> Self {
-        Evaluator{expr: expr, scope: scope, file_path: Some(file_path), out_path: Some(out_path)}
+    pub fn new(expr: &'ast Ast, env: Env, file_path: PathBuf, out_path: PathBuf) -> Self {
+        Evaluator{
+            expr: expr, 
+            env: env, 
+            file_path: Some(file_path), 
+            out_path: Some(out_path), 
+            context: Rc::new(Context::default())
+        }
     }
 
     /// Creates a new evaluator with no input file or output directory specified.
     ///
     /// # Arguments
     /// `expr` - The AST (Abstract Syntax Tree) to be evaluated.
-    /// `scope` - The scope of the evaluation.
-    pub fn without_files(expr: &'ast Ast, scope: Scope<'scope, 'ast>) -> Self {
-        Evaluator{expr: expr, scope: scope, file_path: None, out_path: None}
+    /// `env` - The environment of the evaluation.
+    pub fn without_files(expr: &'ast Ast, env: Env) -> Self {
+        Evaluator{expr: expr, env: env, file_path: None, out_path: None, context: Rc::new(Context::default())}
+    }
+
+    /// Creates a new evaluator that is part of an existing build.
+    /// The input file and output directory might not be specified.
+    ///
+    /// # Arguments
+    /// `expr` - The AST (Abstract Syntax Tree) to be evaluated.
+    /// `env` - The environment of the evaluation.
+    /// `file_path` - The path to the file for which the AST has been evaluated.
+    /// `out_path` - The output directory.
+    /// `context` - The state of the build.
+    pub fn with_context(expr: &'ast Ast, 
+                        env: Env, 
+                        file_path: Option<PathBuf>, 
+                        out_path: Option<PathBuf>, 
+                        context: Rc<Context>) -> Self {
+        Evaluator{expr: expr, env: env, file_path: file_path, out_path: out_path, context: context}
     }
 
-    /// Creates a new `Evaluator` with the same input file, out directory and scope for the given
-    /// expression.
-    pub fn copy_for_expr(&self, expr: &'ast Ast) -> Evaluator<'scope, 'ast> {
+    /// Creates a new `Evaluator` with the same input file, out directory and environment for the
+    /// given expression.
+    pub fn copy_for_expr<'e>(&self, expr: &'e Ast) -> Evaluator<'e> {
         Evaluator {
-            scope: self.scope.clone(),
+            env: self.env.clone(),
             expr: expr,
             file_path: self.file_path.clone(),
-            out_path: self.out_path.clone()
+            out_path: self.out_path.clone(),
+            context: self.context.clone(),
         }
     }
 
-    /// Create a new `Evaluator` with the same input and out directory but with a new scope.
-    pub fn copy_for_child_expr(&self, expr: &'ast Ast, scope: Scope<'scope, 'ast>) -> Evaluator<'scope, 'ast> {
+    /// Create a new `Evaluator` with the same input and out directory but with a new environment.
+    pub fn copy_for_child_expr<'e>(&self, expr: &'e Ast, env: Env) -> Evaluator<'e> {
         Evaluator {
-            scope: scope,
+            env: env,
             expr: expr,
             file_path: self.file_path.clone(),
             out_path: self.out_path.clone(),
+            context: self.context.clone(),
         }
     }
 
+    /// Creates an unevaluated `Thunk` for the given expression, with the same input file, out
+    /// directory and environment.
+    pub fn thunk_for(&self, expr: &Rc<Ast>) -> Thunk {
+        Thunk::new(expr.clone(), 
+                   self.env.clone(), 
+                   self.file_path.clone(), 
+                   self.out_path.clone(),
+                   self.context.clone())
+    }
+
+    /// The expression that this evaluator evaluates.
+    pub fn expr(&self) -> &'ast Ast {
+        self.expr
+    }
+
     pub fn get_working_dir(&self) -> Option<&Path> {
         self.file_path
             .as_ref()
@@ -79,18 +151,61 @@ impl<'scope, 'ast: 'scope> Evaluator<'scope, 'ast> {
     }
 
     /// Evaluates the expression
+    ///
+    /// Calls in tail position are evaluated in a loop instead of recursively, so tail recursive
+    /// functions run in constant stack space. Other nested evaluations grow the stack on demand,
+    /// so the depth is only limited by the maximum depth of the context and not by the stack of
+    /// the calling thread.
+    ///
+    /// # Errors
+    /// `EvalError::RecursionLimit` if the evaluation is nested deeper than the maximum depth of
+    /// the context.
     pub fn eval(&self) -> EvalResult {
+        let _depth = match self.context.enter() {
+            Ok(guard) => guard,
+            Err(err) => { return Err(err); },
+        };
+
+        stacker::maybe_grow(RED_ZONE, STACK_SEGMENT, || {
+            let mut step = self.eval_step();
+            loop {
+                step = match step {
+                    Step::Done(result) => { return result; },
+                    Step::Call(closure, arg, frame) => {
+                        let _frame = self.context.enter_frame(frame);
+                        closure.eval_step(arg)
+                    },
+                };
+            }
+        })
+    }
+
+    /// Evaluates the expression up to the first call in tail position.
+    pub fn eval_step(&self) -> Step {
+        match self.expr {
+            &Ast::Let(ref bindings, ref child_expr) => evaluate_let(self, bindings, child_expr),
+            &Ast::Call(ref func, ref args) => evaluate_call(self, func, args),
+            &Ast::If(ref cond, ref then, ref otherwise) => evaluate_if(self, cond, then, otherwise),
+            _ => Step::Done(self.eval_expr()),
+        }
+    }
+
+    /// Evaluates expressions that don't have any sub-expressions in tail position.
+    fn eval_expr(&self) -> EvalResult {
         match self.expr {
             &Ast::BinOp(ref binop, ref left, ref right) => evaluate_binop(self, binop, left, right),
+            &Ast::UnaryOp(ref op, ref expr) => evaluate_unaryop(self, op, expr),
             &Ast::Val(ref val) => evaluate_val(self, val),
-            &Ast::Set(_) => panic!("Evaluation of sets is not implemented"),
-            &Ast::Let(ref field, ref child_expr) => evaluate_let(self, field, child_expr),
+            &Ast::Set(ref fields) => evaluate_set(self, fields),
             &Ast::Fn(ref param, ref expr) => evaluate_closure(self, param, expr),
-            &Ast::Call(ref func, ref input) => evaluate_call(self, func, input),
             &Ast::Id(ref id) => evaluate_id(self, id),
             &Ast::Import(_, ref relative_path) => evaluate_import(self, relative_path),
+            &Ast::Collection(_, ref dir) => evaluate_collection(self, dir),
+            &Ast::Field(ref expr, ref field) => evaluate_field(self, expr, field),
+            &Ast::List(ref items) => evaluate_list(self, items),
             &Ast::Html{ref tag_name, ref attributes, ref children} => evaluate_html(self, tag_name, attributes, children),
             &Ast::HtmlClosed{ref tag_name, ref attributes} => evaluate_html_closed(self, tag_name, attributes),
+            &Ast::Let(..) | &Ast::Call(..) | &Ast::If(..) => self.eval(),
         }
     }
 }
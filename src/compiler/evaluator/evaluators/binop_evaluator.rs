@@ -1,8 +1,9 @@
+use std::cmp::Ordering;
 use compiler::models::{Ast, BinOp, Output};
 use compiler::evaluator::{Evaluator, EvalResult};
 use compiler::errors::EvalError;
 
-pub fn evaluate_binop<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, binop: &'ast BinOp, left: &'ast Ast, right: &'ast Ast) -> EvalResult {
+pub fn evaluate_binop<'ast>(eval: &Evaluator<'ast>, binop: &'ast BinOp, left: &'ast Ast, right: &'ast Ast) -> EvalResult {
     match binop {
         &BinOp::Add => eval_add(eval, left, right),
         &BinOp::Sub => eval_sub(eval, left, right),
@@ -11,15 +12,22 @@ pub fn evaluate_binop<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, bino
         &BinOp::Mod => eval_mod(eval, left, right),
         &BinOp::Pow => eval_pow(eval, left, right),
         &BinOp::Equals => eval_equal(eval, left, right),
+        &BinOp::NotEquals => eval_not_equal(eval, left, right),
+        &BinOp::Lt => eval_compare(eval, binop, left, right, |ord| ord == Ordering::Less),
+        &BinOp::Gt => eval_compare(eval, binop, left, right, |ord| ord == Ordering::Greater),
+        &BinOp::LtEq => eval_compare(eval, binop, left, right, |ord| ord != Ordering::Greater),
+        &BinOp::GtEq => eval_compare(eval, binop, left, right, |ord| ord != Ordering::Less),
+        &BinOp::And => eval_and(eval, left, right),
+        &BinOp::Or => eval_or(eval, left, right),
     }
 }
 
-fn eval_add<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, right: &Ast) -> EvalResult {
+fn eval_add<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
     let left = eval.copy_for_expr(left).eval();
     let right = eval.copy_for_expr(right).eval();
 
     match (left, right) {
-        (Ok(Output::Int(left)), Ok(Output::Int(right))) => Ok(Output::Int(left + right)),
+        (Ok(Output::Int(left)), Ok(Output::Int(right))) => checked_int_op(BinOp::Add, left, right, i64::checked_add),
         (Ok(Output::Double(left)), Ok(Output::Int(right))) => Ok(Output::Double(left + right as f64)),
         (Ok(Output::Int(left)), Ok(Output::Double(right))) => Ok(Output::Double(left as f64 + right)),
         (Ok(Output::Int(left)), Ok(Output::String(right))) => Ok(Output::String(format!("{}{}", left, right))),
@@ -35,12 +43,12 @@ fn eval_add<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, ri
     }
 }
 
-fn eval_sub<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, right: &Ast) -> EvalResult {
+fn eval_sub<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
     let left = eval.copy_for_expr(left).eval();
     let right = eval.copy_for_expr(right).eval();
 
     match (left, right) {
-        (Ok(Output::Int(left)), Ok(Output::Int(right))) => Ok(Output::Int(left - right)),
+        (Ok(Output::Int(left)), Ok(Output::Int(right))) => checked_int_op(BinOp::Sub, left, right, i64::checked_sub),
         (Ok(Output::Double(left)), Ok(Output::Int(right))) => Ok(Output::Double(left - right as f64)),
         (Ok(Output::Int(left)), Ok(Output::Double(right))) => Ok(Output::Double(left as f64 - right)),
         (Ok(l), Ok(r)) => Err(EvalError::InvalidBinOp(BinOp::Sub, l, r)),
@@ -49,12 +57,12 @@ fn eval_sub<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, ri
     }
 }
 
-fn eval_mul<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, right: &Ast) -> EvalResult {
+fn eval_mul<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
     let left = eval.copy_for_expr(left).eval();
     let right = eval.copy_for_expr(right).eval();
 
     match (left, right) {
-        (Ok(Output::Int(left)), Ok(Output::Int(right))) => Ok(Output::Int(left * right)),
+        (Ok(Output::Int(left)), Ok(Output::Int(right))) => checked_int_op(BinOp::Mul, left, right, i64::checked_mul),
         (Ok(Output::Double(left)), Ok(Output::Int(right))) => Ok(Output::Double(left * right as f64)),
         (Ok(Output::Int(left)), Ok(Output::Double(right))) => Ok(Output::Double(left as f64 * right)),
         (Ok(l), Ok(r)) => Err(EvalError::InvalidBinOp(BinOp::Mul, l, r)),
@@ -63,12 +71,14 @@ fn eval_mul<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, ri
     }
 }
 
-fn eval_div<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, right: &Ast) -> EvalResult {
+fn eval_div<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
     let left = eval.copy_for_expr(left).eval();
     let right = eval.copy_for_expr(right).eval();
 
     match (left, right) {
-        (Ok(Output::Int(left)), Ok(Output::Int(right))) => Ok(Output::Int(left / right)),
+        (Ok(Output::Int(left)), Ok(Output::Int(0))) => 
+            Err(EvalError::DivisionByZero(BinOp::Div, Output::Int(left), Output::Int(0))),
+        (Ok(Output::Int(left)), Ok(Output::Int(right))) => checked_int_op(BinOp::Div, left, right, i64::checked_div),
         (Ok(Output::Double(left)), Ok(Output::Int(right))) => Ok(Output::Double(left / right as f64)),
         (Ok(Output::Int(left)), Ok(Output::Double(right))) => Ok(Output::Double(left as f64 / right)),
         (Ok(l), Ok(r)) => Err(EvalError::InvalidBinOp(BinOp::Div, l, r)),
@@ -77,12 +87,14 @@ fn eval_div<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, ri
     }
 }
 
-fn eval_mod<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, right: &Ast) -> EvalResult {
+fn eval_mod<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
     let left = eval.copy_for_expr(left).eval();
     let right = eval.copy_for_expr(right).eval();
 
     match (left, right) {
-        (Ok(Output::Int(left)), Ok(Output::Int(right))) => Ok(Output::Int(left % right)),
+        (Ok(Output::Int(left)), Ok(Output::Int(0))) => 
+            Err(EvalError::DivisionByZero(BinOp::Mod, Output::Int(left), Output::Int(0))),
+        (Ok(Output::Int(left)), Ok(Output::Int(right))) => checked_int_op(BinOp::Mod, left, right, i64::checked_rem),
         (Ok(Output::Double(left)), Ok(Output::Int(right))) => Ok(Output::Double(left % right as f64)),
         (Ok(Output::Int(left)), Ok(Output::Double(right))) => Ok(Output::Double(left as f64 % right)),
         (Ok(l), Ok(r)) => Err(EvalError::InvalidBinOp(BinOp::Mod, l, r)),
@@ -91,20 +103,108 @@ fn eval_mod<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, ri
     }
 }
 
-fn eval_pow<'scope, 'ast: 'scope>(_eval: &Evaluator<'scope, 'ast>, _left: &Ast, _right: &Ast) -> EvalResult {
-    panic!("Powers not yet supported");
+fn eval_pow<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
+    let left = eval.copy_for_expr(left).eval();
+    let right = eval.copy_for_expr(right).eval();
+
+    match (left, right) {
+        (Ok(Output::Int(left)), Ok(Output::Int(right))) => int_pow(left, right),
+        (Ok(Output::Double(left)), Ok(Output::Double(right))) => Ok(Output::Double(left.powf(right))),
+        (Ok(Output::Double(left)), Ok(Output::Int(right))) => Ok(Output::Double(left.powf(right as f64))),
+        (Ok(Output::Int(left)), Ok(Output::Double(right))) => Ok(Output::Double((left as f64).powf(right))),
+        (Ok(l), Ok(r)) => Err(EvalError::InvalidBinOp(BinOp::Pow, l, r)),
+        (Err(err), _) => Err(err),
+        (_, Err(err)) => Err(err),
+    }
+}
+
+/// Raises an integer to an integer power.
+/// The result is an integer for non-negative exponents and a double for negative exponents,
+/// since e.g. `** 2 -1` is `0.5`.
+fn int_pow(base: i64, exp: i64) -> EvalResult {
+    if exp < 0 {
+        if base == 0 {
+            Err(EvalError::DivisionByZero(BinOp::Pow, Output::Int(base), Output::Int(exp)))
+        } else {
+            Ok(Output::Double((base as f64).powf(exp as f64)))
+        }
+    } else if exp > u32::max_value() as i64 {
+        Err(EvalError::ArithmeticOverflow(BinOp::Pow, Output::Int(base), Output::Int(exp)))
+    } else {
+        checked_int_op(BinOp::Pow, base, exp, |base, exp| base.checked_pow(exp as u32))
+    }
 }
 
-fn eval_equal<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, left: &Ast, right: &Ast) -> EvalResult {
+/// Applies a checked integer operation and returns `EvalError::ArithmeticOverflow` if it
+/// overflows.
+fn checked_int_op<F>(op: BinOp, left: i64, right: i64, checked_op: F) -> EvalResult 
+    where F: FnOnce(i64, i64) -> Option<i64> {
+    match checked_op(left, right) {
+        Some(result) => Ok(Output::Int(result)),
+        None => Err(EvalError::ArithmeticOverflow(op, Output::Int(left), Output::Int(right))),
+    }
+}
+
+fn eval_equal<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
+    equals(eval, left, right).map(Output::Bool)
+}
+
+fn eval_not_equal<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
+    equals(eval, left, right).map(|equal| Output::Bool(!equal))
+}
+
+fn equals<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> Result<bool, EvalError> {
     let left = eval.copy_for_expr(left).eval();
     let right = eval.copy_for_expr(right).eval();
 
     match (left, right) {
-        (Ok(Output::Int(left)), Ok(Output::Int(right))) => Ok(Output::Bool(left == right)),
-        (Ok(Output::Double(left)), Ok(Output::Int(right))) => Ok(Output::Bool(left == right as f64)),
-        (Ok(Output::Int(left)), Ok(Output::Double(right))) => Ok(Output::Bool(left as f64 == right)),
-        (Ok(l), Ok(r)) => Err(EvalError::InvalidBinOp(BinOp::Equals, l, r)),
+        (Ok(l), Ok(r)) => l.equals(&r),
         (Err(err), _) => Err(err),
         (_, Err(err)) => Err(err),
     }
 }
+
+/// Evaluates an ordering comparison. `is_true` decides from the ordering of the operands whether
+/// the comparison holds.
+fn eval_compare<'ast, F>(eval: &Evaluator<'ast>, op: &BinOp, left: &Ast, right: &Ast, is_true: F) -> EvalResult 
+    where F: Fn(Ordering) -> bool {
+    let left = eval.copy_for_expr(left).eval();
+    let right = eval.copy_for_expr(right).eval();
+
+    match (left, right) {
+        (Ok(l), Ok(r)) => {
+            match l.compare(&r) {
+                Ok(ordering) => Ok(Output::Bool(is_true(ordering))),
+                Err(_) => Err(EvalError::InvalidBinOp(op.clone(), l, r)),
+            }
+        },
+        (Err(err), _) => Err(err),
+        (_, Err(err)) => Err(err),
+    }
+}
+
+/// Short-circuiting `and`: the right operand is only evaluated if the left one is `true`.
+fn eval_and<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
+    match eval_bool(eval, left) {
+        Ok(true) => eval_bool(eval, right).map(Output::Bool),
+        Ok(false) => Ok(Output::Bool(false)),
+        Err(err) => Err(err),
+    }
+}
+
+/// Short-circuiting `or`: the right operand is only evaluated if the left one is `false`.
+fn eval_or<'ast>(eval: &Evaluator<'ast>, left: &Ast, right: &Ast) -> EvalResult {
+    match eval_bool(eval, left) {
+        Ok(true) => Ok(Output::Bool(true)),
+        Ok(false) => eval_bool(eval, right).map(Output::Bool),
+        Err(err) => Err(err),
+    }
+}
+
+fn eval_bool<'ast>(eval: &Evaluator<'ast>, expr: &Ast) -> Result<bool, EvalError> {
+    match eval.copy_for_expr(expr).eval() {
+        Ok(Output::Bool(b)) => Ok(b),
+        Ok(not_bool) => Err(EvalError::NotABool(not_bool)),
+        Err(err) => Err(err),
+    }
+}
@@ -1,19 +1,44 @@
-use compiler::models::{Ast, Output};
-use compiler::evaluator::{Evaluator, EvalResult};
+use std::rc::Rc;
+use compiler::models::{Ast, Arg, Output};
+use compiler::evaluator::{Evaluator, Step, StackFrame};
 use compiler::errors::EvalError;
 
 /// Evaluates a function call
 /// # Arguments
 /// `func` - the expression that returns a function
-/// `input` - the expression that returns the parameter for the function
-pub fn evaluate_call<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, func: &'ast Ast,  input: &'ast Ast) -> EvalResult {
-    let func = eval.copy_for_expr(func).eval();
-    if let Ok(Output::Fn(func)) = func {
-        func.eval(input)
-    } else if let Ok(not_func) = func {
-        Err(EvalError::NotAFunction(not_func))
-    } else {
-        func
+/// `args` - the arguments, the function is applied to one after the other. The call of the last
+/// argument is in tail position.
+pub fn evaluate_call<'ast>(eval: &Evaluator<'ast>, func: &'ast Rc<Ast>, args: &'ast Vec<Arg>) -> Step {
+    let mut func_result = eval.copy_for_expr(func).eval();
+    for (i, arg) in args.iter().enumerate() {
+        let is_last = i + 1 == args.len();
+        func_result = match func_result {
+            Ok(Output::Fn(closure)) => {
+                let frame = StackFrame::Call(func.clone(), arg.0);
+                if is_last {
+                    // The call itself is left to the caller, so that calls in tail position
+                    // don't use any stack.
+                    return Step::Call(closure, eval.thunk_for(&arg.1), frame);
+                }
+                let _frame = eval.context.enter_frame(frame);
+                closure.eval(eval.thunk_for(&arg.1))
+            },
+            Ok(Output::Builtin(builtin)) => {
+                // Builtin functions are native so their arguments are evaluated eagerly.
+                eval.copy_for_expr(&arg.1)
+                    .eval()
+                    .and_then(|arg| { builtin.apply(arg) })
+            },
+            // If the function has already been applied to the previous arguments then it has
+            // been called with too many arguments.
+            Ok(not_func) => {
+                if i > 0 {
+                    return Step::Done(Err(EvalError::TooManyArguments(arg.0, not_func)));
+                }
+                return Step::Done(Err(EvalError::NotAFunction(not_func)));
+            },
+            Err(err) => { return Step::Done(Err(err)); },
+        };
     }
-
+    Step::Done(func_result)
 }
@@ -1,11 +1,21 @@
+use std::rc::Rc;
 use compiler::models::{Ast, Closure, Output};
 use compiler::evaluator::{Evaluator, EvalResult};
 
-pub fn evaluate_closure<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, param: &str, expr: &Ast) -> EvalResult {
+/// Evaluates a function definition.
+/// Only the variables that the function uses are captured, and they are not evaluated until the
+/// function needs them.
+pub fn evaluate_closure<'ast>(eval: &Evaluator<'ast>, param: &str, expr: &Rc<Ast>) -> EvalResult {
+    let mut free = expr.free_variables();
+    free.remove(param);
+
     let closure = Closure::new(
         param.to_string(), 
         expr.clone(), 
-        eval.scope.to_closed()
+        eval.env.capture(&free),
+        eval.file_path.clone(),
+        eval.out_path.clone(),
+        eval.context.clone()
     );
     Ok(Output::Fn(closure))
 }
@@ -0,0 +1,165 @@
+use std::ffi::OsStr;
+use std::fs::{self, File, create_dir_all};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use compiler::evaluator::{Evaluator, EvalResult, Context};
+use compiler::evaluator::builtins::sort_keyed;
+use compiler::errors::EvalError;
+use compiler::models::{Output, Thunk};
+use compiler::evaluate_contents_with_context;
+use compiler::front_matter::{split_front_matter, parse_front_matter};
+use compiler::markdown::{is_markdown_file, CONTENT_FIELD};
+use super::build_page;
+
+/// The field of a collection item that holds the path of the built page.
+const URL_FIELD: &'static str = "url";
+
+/// The field of a collection item that identifies the page. Defaults to the file name without
+/// the extension.
+const SLUG_FIELD: &'static str = "slug";
+
+/// The field of a collection item that the items are sorted by.
+const DATE_FIELD: &'static str = "date";
+
+/// Evaluates `collection <dir>`.
+///
+/// Every `.foil` and `.md` file in `dir` is built into the output directory like a path literal.
+/// Returns a list with a set for every file, which holds the fields of the front matter of the
+/// file, the field `url` with the path of the built page and the field `slug`. The list is
+/// sorted by the `date` field, files without a date come first. Files with the same date are
+/// sorted by name.
+///
+/// # Errors
+/// `EvalError::PathNotRelative` if `dir` is an absolute path.
+/// `EvalError::NotFile` if `dir` is not a directory.
+/// `EvalError::OutputPathNotSpecified` if the evaluator has no output path.
+/// `EvalError::NotComparable` if the dates of two files can't be compared.
+/// Any error that occurs while building the files.
+pub fn evaluate_collection<'ast>(eval: &Evaluator<'ast>, dir: &str) -> EvalResult {
+    if Path::new(dir).is_absolute() {
+        return Err(EvalError::PathNotRelative(dir.to_string()));
+    }
+    let out_path = match eval.out_path {
+        Some(ref out_path) => out_path,
+        None => { return Err(EvalError::OutputPathNotSpecified); },
+    };
+    let fallback_dir = PathBuf::from("./");
+    let working_dir = eval.get_working_dir().unwrap_or(&fallback_dir);
+
+    let files = match content_files(&working_dir.join(dir)) {
+        Ok(files) => files,
+        Err(err) => { return Err(err); },
+    };
+    if let Err(err) = create_dir_all(out_path.join(dir)) {
+        return Err(EvalError::IO(err));
+    }
+
+    let mut items = Vec::new();
+    for file in files.iter() {
+        match collection_item(file, dir, out_path, &eval.context) {
+            Ok(item) => items.push(item),
+            Err(err) => { return Err(err); },
+        }
+    }
+    sort_by_date(items)
+}
+
+/// Returns the `.foil` and `.md` files in the directory, sorted by name.
+fn content_files(dir: &Path) -> Result<Vec<PathBuf>, EvalError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => {
+            return Err(EvalError::NotFile(vec![dir.to_str().unwrap_or("None").to_string()]));
+        },
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(err) => { return Err(EvalError::IO(err)); },
+        };
+        let extension = path.extension().and_then(OsStr::to_str);
+        if path.is_file() && (extension == Some("foil") || extension == Some("md")) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Builds the file and returns the set of its front matter, url and slug.
+fn collection_item(file: &Path, dir: &str, out_path: &Path, context: &Rc<Context>) -> EvalResult {
+    let mut contents = String::new();
+    let read_res = File::open(file).and_then(|mut f| f.read_to_string(&mut contents));
+    if let Err(err) = read_res {
+        return Err(EvalError::IO(err));
+    }
+
+    // Markdown files are rendered into a set of their front matter and content, so their front
+    // matter is taken from the rendered page instead of being parsed again.
+    let mut rendered = None;
+    let file_name = file.file_name().and_then(OsStr::to_str).unwrap_or("None");
+    let relative_path = Path::new(dir).join(file_name);
+    let url = build_page(&relative_path, out_path, |out_dir| {
+        let output = evaluate_contents_with_context(&contents, file, out_dir, context.clone());
+        if let Ok(Output::Set(ref fields)) = output {
+            if is_markdown_file(file) {
+                rendered = Some(fields.clone());
+            }
+        }
+        output
+    });
+    let url = match url {
+        Ok(url) => url,
+        Err(err) => { return Err(err); },
+    };
+
+    let mut fields = match rendered {
+        Some(mut fields) => {
+            fields.remove(CONTENT_FIELD);
+            fields
+        },
+        None => {
+            let (front_matter, _) = split_front_matter(&contents);
+            match parse_front_matter(front_matter, file) {
+                Ok(fields) => fields,
+                Err(err) => { return Err(err); },
+            }
+        },
+    };
+
+    if !fields.contains_key(SLUG_FIELD) {
+        let slug = file.file_stem().and_then(OsStr::to_str).unwrap_or("None").to_string();
+        fields.insert(SLUG_FIELD.to_string(), Thunk::evaluated(Output::String(slug)));
+    }
+    fields.insert(URL_FIELD.to_string(), Thunk::evaluated(url));
+    Ok(Output::Set(fields))
+}
+
+/// Sorts the items by their `date` field. Items without a date come first.
+fn sort_by_date(items: Vec<Output>) -> EvalResult {
+    let mut undated = Vec::new();
+    let mut dated = Vec::new();
+    for item in items.into_iter() {
+        let date = match item {
+            Output::Set(ref fields) => fields.get(DATE_FIELD).map(Thunk::eval),
+            _ => None,
+        };
+        match date {
+            Some(Err(err)) => { return Err(err); },
+            Some(Ok(date)) => dated.push((date, item)),
+            None => undated.push(item),
+        }
+    }
+
+    match sort_keyed(dated) {
+        Ok(dated) => {
+            undated.extend(dated);
+            Ok(Output::List(undated))
+        },
+        Err(err) => Err(err),
+    }
+}
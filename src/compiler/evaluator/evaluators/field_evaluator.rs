@@ -0,0 +1,18 @@
+use compiler::models::{Ast, Id, Output};
+use compiler::evaluator::{Evaluator, EvalResult};
+use compiler::errors::EvalError;
+
+/// Evaluates a field access, i.e. looks up the field `field` in the set returned by `expr`.
+pub fn evaluate_field<'ast>(eval: &Evaluator<'ast>, expr: &'ast Ast, field: &Id) -> EvalResult {
+    match eval.copy_for_expr(expr).eval() {
+        Ok(Output::Set(fields)) => {
+            if let Some(thunk) = fields.get(&field.1) {
+                thunk.eval()
+            } else {
+                Err(EvalError::FieldNotFound(Clone::clone(field)))
+            }
+        },
+        Ok(not_set) => Err(EvalError::NotASet(not_set)),
+        Err(err) => Err(err),
+    }
+}
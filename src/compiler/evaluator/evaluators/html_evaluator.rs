@@ -2,7 +2,7 @@ use compiler::models::{Ast, SetField, Output};
 use compiler::evaluator::{Evaluator, EvalResult};
 use compiler::errors::EvalError;
 
-pub fn evaluate_html<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, tag_name: &str, attributes: &Vec<SetField>, children: &Vec<Ast>) -> EvalResult {
+pub fn evaluate_html<'ast>(eval: &Evaluator<'ast>, tag_name: &str, attributes: &Vec<SetField>, children: &Vec<Ast>) -> EvalResult {
     let children = children
         .iter()
         .map(|child|{ 
@@ -21,7 +21,7 @@ pub fn evaluate_html<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, tag_n
     }
 }
 
-pub fn evaluate_html_closed<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, tag_name: &str, attributes: &Vec<SetField>) -> EvalResult {
+pub fn evaluate_html_closed<'ast>(eval: &Evaluator<'ast>, tag_name: &str, attributes: &Vec<SetField>) -> EvalResult {
     let attributes = eval_attributes(eval, attributes);
     match attributes {
         Ok(attributes) => Ok(Output::String(format!("<{}{}/>", tag_name, attributes))),
@@ -31,7 +31,7 @@ pub fn evaluate_html_closed<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>
 
 type StrRes = Result<String,  EvalError>;
 
-fn eval_attributes<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, attributes: &Vec<SetField>) -> StrRes {
+fn eval_attributes<'ast>(eval: &Evaluator<'ast>, attributes: &Vec<SetField>) -> StrRes {
     let attributes = attributes
         .iter()
         .map(|field|{
@@ -62,7 +62,7 @@ fn fold_string_result<F>(out_str: StrRes, next_string: StrRes, combinator: F) ->
     }
 }
 
-fn field_to_attribute_string<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, field: &SetField) -> StrRes {
+fn field_to_attribute_string<'ast>(eval: &Evaluator<'ast>, field: &SetField) -> StrRes {
     let evaluator = eval.copy_for_expr(&field.value);
     let result = evaluator.eval();
     let result = result.and_then(Output::to_string);
@@ -2,10 +2,10 @@ use compiler::models::Id;
 use compiler::evaluator::{Evaluator, EvalResult};
 use compiler::errors::EvalError;
 
-/// Evaluates an "Id" (a function or  a variable reference) by doing a lookup in the scope
-pub fn evaluate_id<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, id: &Id) -> EvalResult {
+/// Evaluates an "Id" (a function or  a variable reference) by doing a lookup in the environment
+pub fn evaluate_id<'ast>(eval: &Evaluator<'ast>, id: &Id) -> EvalResult {
     let id_name: &str = &id.1;
-    if let Some(val) = eval.scope.get_value(id_name) {
+    if let Some(val) = eval.env.get_value(id_name) {
         val
     } else {
         Err(EvalError::IdNotFound(Clone::clone(id)))
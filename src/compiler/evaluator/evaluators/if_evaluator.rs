@@ -0,0 +1,16 @@
+use compiler::models::{Ast, Output};
+use compiler::evaluator::{Evaluator, Step};
+use compiler::errors::EvalError;
+
+/// Evaluates a conditional expression.
+/// Only the branch that is taken is evaluated, so side effects (such as copying files) of the
+/// other branch never happen.
+/// The branches are in tail position.
+pub fn evaluate_if<'ast>(eval: &Evaluator<'ast>, cond: &'ast Ast, then: &'ast Ast, otherwise: &'ast Ast) -> Step {
+    match eval.copy_for_expr(cond).eval() {
+        Ok(Output::Bool(true)) => eval.copy_for_expr(then).eval_step(),
+        Ok(Output::Bool(false)) => eval.copy_for_expr(otherwise).eval_step(),
+        Ok(not_bool) => Step::Done(Err(EvalError::NotABool(not_bool))),
+        Err(err) => Step::Done(Err(err)),
+    }
+}
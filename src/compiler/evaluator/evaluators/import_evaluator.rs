@@ -1,18 +1,54 @@
 use compiler::evaluator::{Evaluator, EvalResult};
-use compiler::{evaluate_file};
+use compiler::errors::EvalError;
+use compiler::{evaluate_file_with_context};
 use std::path::{PathBuf, Path};
 
-pub fn evaluate_import<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, file_name: &str) -> EvalResult {
-    let fallback_dir = PathBuf::from("./");
+/// The prefix of imports that are resolved relative to the project root.
+const ROOT_PREFIX: &'static str = "@/";
 
-    let import_file = eval
-        .get_working_dir()
-        .unwrap_or(&fallback_dir)
-        .join(file_name);
+/// Evaluates the imported file.
+///
+/// Imports that start with `@/` are resolved relative to the project root. Other imports are
+/// resolved relative to the directory of the importing file and then relative to each directory
+/// of the search path.
+///
+/// # Errors
+/// `EvalError::NotFile` with every location that was tried if none of them is a file.
+pub fn evaluate_import<'ast>(eval: &Evaluator<'ast>, file_name: &str) -> EvalResult {
+    let fallback_dir = PathBuf::from("./");
 
     let out_dir = eval.out_path
         .as_ref()
         .unwrap_or(&fallback_dir);
 
-    evaluate_file(&import_file, out_dir)
+    let candidates = import_candidates(eval, file_name, &fallback_dir);
+    match candidates.iter().find(|candidate| candidate.is_file()) {
+        Some(import_file) => evaluate_file_with_context(import_file, out_dir, eval.context.clone()),
+        None => {
+            let tried = candidates
+                .iter()
+                .map(|candidate| candidate.to_str().unwrap_or("None").to_string())
+                .collect();
+            Err(EvalError::NotFile(tried))
+        },
+    }
+}
+
+/// Returns the locations where the imported file is looked for, in order of priority.
+fn import_candidates<'ast>(eval: &Evaluator<'ast>, file_name: &str, fallback_dir: &Path) -> Vec<PathBuf> {
+    if file_name.starts_with(ROOT_PREFIX) {
+        let root = eval.context.root
+            .as_ref()
+            .map(PathBuf::as_path)
+            .unwrap_or(fallback_dir);
+        return vec![root.join(&file_name[ROOT_PREFIX.len()..])];
+    }
+
+    let mut candidates = vec![eval.get_working_dir().unwrap_or(fallback_dir).join(file_name)];
+    if Path::new(file_name).is_relative() {
+        for dir in &eval.context.search_path {
+            candidates.push(dir.join(file_name));
+        }
+    }
+    candidates
 }
@@ -1,11 +1,155 @@
-use compiler::models::{Ast, SetField};
-use compiler::evaluator::{Evaluator, EvalResult, Scope, OpenScope};
+use compiler::models::{Ast, SetField, Thunk};
+use compiler::evaluator::{Evaluator, Step};
+use std::cmp;
 use std::collections::HashMap;
+use std::mem;
 
-pub fn evaluate_let<'scope, 'ast: 'scope>(eval: &Evaluator<'scope, 'ast>, field: &SetField, child_expr: &Ast) -> EvalResult {
-    let mut map: HashMap<&str, _> = HashMap::new();
-    map.insert(&field.name, eval.copy_for_expr(&field.value));
-    let child_scope = OpenScope{ parent: Some(eval.scope.clone()), map: map};
-    let eval = eval.copy_for_child_expr(child_expr, Scope::Open(&child_scope));
-    eval.eval()
+/// Evaluates a let expression.
+/// The bindings can refer to themselves and to each other. Bindings that don't are evaluated in
+/// the environment of the bindings they use, so that only the values that really refer back to
+/// their own definitions, e.g. recursive functions, need a recursive environment.
+/// The child expression is in tail position.
+pub fn evaluate_let<'ast>(eval: &Evaluator<'ast>, bindings: &'ast Vec<SetField>, child_expr: &'ast Ast) -> Step {
+    let mut env = eval.env.clone();
+    let mut vars = HashMap::new();
+    for group in binding_groups(bindings) {
+        // Bindings that don't use each other share a frame.
+        let uses_vars = group.uses.iter().any(|name| vars.contains_key(*name));
+        if (group.recursive || uses_vars) && !vars.is_empty() {
+            env = env.with_vars(mem::replace(&mut vars, HashMap::new()));
+        }
+
+        if group.recursive {
+            let exprs = group.bindings.iter()
+                .map(|binding| (binding.name.clone(), binding.value.clone()))
+                .collect();
+            env = env.with_recursive_vars(exprs,
+                                          eval.file_path.clone(),
+                                          eval.out_path.clone(),
+                                          eval.context.clone());
+        } else {
+            let binding = group.bindings[0];
+            let thunk = Thunk::new(binding.value.clone(),
+                                   env.clone(),
+                                   eval.file_path.clone(),
+                                   eval.out_path.clone(),
+                                   eval.context.clone());
+            vars.insert(binding.name.clone(), thunk);
+        }
+    }
+    if !vars.is_empty() {
+        env = env.with_vars(vars);
+    }
+    let eval = eval.copy_for_child_expr(child_expr, env);
+    eval.eval_step()
+}
+
+/// Bindings of a let expression that refer to each other.
+struct Group<'b> {
+    bindings: Vec<&'b SetField>,
+
+    /// The names of the bindings of the let expression that the group uses.
+    uses: Vec<&'b str>,
+
+    /// `true` if the bindings refer to themselves or to each other.
+    recursive: bool,
+}
+
+/// Splits the bindings into groups of bindings that refer to each other. Every group only uses
+/// itself and the groups before it. If a name is bound several times, the last binding is used.
+fn binding_groups<'b>(bindings: &'b Vec<SetField>) -> Vec<Group<'b>> {
+    let mut indices = HashMap::new();
+    for (i, binding) in bindings.iter().enumerate() {
+        indices.insert(binding.name.as_str(), i);
+    }
+    let uses: Vec<Vec<usize>> = bindings.iter()
+        .map(|binding| {
+            binding.value.free_variables()
+                .iter()
+                .filter_map(|name| indices.get(name.as_str()).cloned())
+                .collect()
+        })
+        .collect();
+
+    let mut components = Components{
+        uses: &uses,
+        visited: 0,
+        order: vec![None; bindings.len()],
+        low: vec![0; bindings.len()],
+        stack: Vec::new(),
+        on_stack: vec![false; bindings.len()],
+        components: Vec::new(),
+    };
+    for (i, binding) in bindings.iter().enumerate() {
+        if indices[binding.name.as_str()] == i && components.order[i].is_none() {
+            components.visit(i);
+        }
+    }
+
+    components.components.into_iter()
+        .map(|component| {
+            let recursive = component.len() > 1 || uses[component[0]].contains(&component[0]);
+            let mut group_uses = Vec::new();
+            for i in component.iter() {
+                group_uses.extend(uses[*i].iter().map(|used| bindings[*used].name.as_str()));
+            }
+            Group{
+                bindings: component.iter().map(|i| &bindings[*i]).collect(),
+                uses: group_uses,
+                recursive: recursive,
+            }
+        })
+        .collect()
+}
+
+/// Finds the strongly connected components of the bindings, i.e. the bindings that use each
+/// other, with Tarjan's algorithm. A component is only completed after all the components it
+/// uses.
+struct Components<'u> {
+    /// The indices of the bindings that each binding uses.
+    uses: &'u Vec<Vec<usize>>,
+    visited: usize,
+    order: Vec<Option<usize>>,
+    low: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    components: Vec<Vec<usize>>,
+}
+
+impl<'u> Components<'u> {
+    fn visit(&mut self, node: usize) {
+        let order = self.visited;
+        self.visited += 1;
+        self.order[node] = Some(order);
+        self.low[node] = order;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        let uses = self.uses;
+        for &next in uses[node].iter() {
+            match self.order[next] {
+                None => {
+                    self.visit(next);
+                    self.low[node] = cmp::min(self.low[node], self.low[next]);
+                },
+                Some(next_order) if self.on_stack[next] => {
+                    self.low[node] = cmp::min(self.low[node], next_order);
+                },
+                Some(_) => {},
+            }
+        }
+
+        if self.low[node] == order {
+            let mut component = Vec::new();
+            loop {
+                let member = self.stack.pop().unwrap();
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
 }
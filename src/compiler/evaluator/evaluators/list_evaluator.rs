@@ -0,0 +1,12 @@
+use compiler::models::{Ast, Output};
+use compiler::evaluator::{Evaluator, EvalResult};
+
+/// Evaluates every expression of a list literal in order.
+/// Returns the first error if any of the expressions fail to evaluate.
+pub fn evaluate_list<'ast>(eval: &Evaluator<'ast>, items: &'ast Vec<Ast>) -> EvalResult {
+    items
+        .iter()
+        .map(|item| { eval.copy_for_expr(item).eval() })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Output::List)
+}
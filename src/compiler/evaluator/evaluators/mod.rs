@@ -1,19 +1,31 @@
 mod binop_evaluator;
+mod unaryop_evaluator;
 mod path_evaluator;
 mod html_evaluator;
 mod import_evaluator;
+mod collection_evaluator;
 mod closure_evaluator;
 mod call_evaluator;
 mod val_evaluator;
 mod id_evaluator;
 mod let_evaluator;
+mod set_evaluator;
+mod field_evaluator;
+mod list_evaluator;
+mod if_evaluator;
 
 pub use self::binop_evaluator::evaluate_binop;
-pub use self::path_evaluator::evaluate_path;
+pub use self::unaryop_evaluator::evaluate_unaryop;
+pub use self::path_evaluator::{evaluate_path, build_path, build_page, is_page};
 pub use self::html_evaluator::{evaluate_html, evaluate_html_closed};
 pub use self::import_evaluator::evaluate_import;
+pub use self::collection_evaluator::evaluate_collection;
 pub use self::closure_evaluator::evaluate_closure;
 pub use self::call_evaluator::evaluate_call;
 pub use self::val_evaluator::evaluate_val;
 pub use self::id_evaluator::evaluate_id;
 pub use self::let_evaluator::evaluate_let;
+pub use self::set_evaluator::evaluate_set;
+pub use self::field_evaluator::evaluate_field;
+pub use self::list_evaluator::evaluate_list;
+pub use self::if_evaluator::evaluate_if;
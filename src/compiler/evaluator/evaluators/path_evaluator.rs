@@ -1,32 +1,138 @@
 // TODO: Maybe this file should be moved to another module?
-use compiler::evaluator::EvalResult;
+use compiler::evaluator::{EvalResult, Context};
 use compiler::errors::EvalError;
 use compiler::models::Output;
-use compiler::{evaluate_file, write_to_file, copy_file};
+use compiler::{evaluate_file_with_context, write_to_file, copy_file};
+use compiler::markdown::{is_markdown_file, CONTENT_FIELD};
 use std::path::Path;
+use std::rc::Rc;
 use std::ffi::OsStr;
+use std::fs::create_dir_all;
+use std::io::Error as IOError;
+use glob::{glob, Pattern};
 
 /// # Arguments
 /// `file` - relative path to the file to be processesed.
 /// `src_path` - current working directory.
 /// `out_path` - the directory to  which the processed file would be copied.
+/// `context` - the state of the build.
 /// 
 /// # Errors
-/// `EvalError::NotFile(String)` will be returned if `file` is not a file or doesn't exist
+/// `EvalError::NotFile` will be returned if `file` is not a file or doesn't exist
 /// relative to `src_path`.
 ///
 /// `EvalError::OutputPathNotSpecified` if `out_path` is None.
 /// error variant would be returned.
+///
+/// `EvalError::InvalidGlob` if `file` is a glob pattern, e.g. `gallery/*.jpg`, that is not
+/// valid or that matches files outside of `src_path`. Glob patterns evaluate to a list of the
+/// built files.
 /// 
-pub fn evaluate_path(file: &str, src_path: &Path, out_path: &Option<&Path>) -> EvalResult {
+pub fn evaluate_path(file: &str, src_path: &Path, out_path: &Option<&Path>, context: &Rc<Context>) -> EvalResult {
     if out_path == &None {
         return Err(EvalError::OutputPathNotSpecified)
     }
     let out_path = out_path.unwrap();
-    build_path(file, src_path, out_path)
+    if is_glob(file) {
+        build_glob(file, src_path, out_path, context)
+    } else {
+        build_path(file, src_path, out_path, context)
+    }
+}
+
+/// Returns `true` if the path is a glob pattern, i.e. if it contains the wildcard `*`.
+/// The wildcards `?` and `[..]` are only supported in patterns that contain a `*`, so that paths
+/// like `img/[old].png` still refer to a single file.
+fn is_glob(file: &str) -> bool {
+    file.contains('*')
+}
+
+/// Builds or copies every file that matches the glob pattern `pattern` relative to `src_path`
+/// and returns a list of the paths of the output files relative to `out_path`, sorted by path.
+/// Directories are created in `out_path` as needed.
+fn build_glob(pattern: &str, src_path: &Path, out_path: &Path, context: &Rc<Context>) -> EvalResult {
+    if Path::new(pattern).is_absolute() {
+        return Err(EvalError::PathNotRelative(pattern.to_string()));
+    }
+
+    // Matches are compared with the canonical source directory, so that patterns like `../*.png`
+    // can't match files outside of it.
+    let src_path = match src_path.canonicalize() {
+        Ok(src_path) => src_path,
+        Err(err) => { return Err(EvalError::IO(err)); },
+    };
+    let full_pattern = match src_path.to_str() {
+        Some(src_dir) => Path::new(&Pattern::escape(src_dir)).join(pattern),
+        None => { return Err(not_unicode(pattern, &src_path)); },
+    };
+    let paths = match full_pattern.to_str().map(glob) {
+        Some(Ok(paths)) => paths,
+        Some(Err(err)) => { return Err(EvalError::InvalidGlob(pattern.to_string(), err.msg.to_string())); },
+        None => { return Err(not_unicode(pattern, &full_pattern)); },
+    };
+
+    let mut files = Vec::new();
+    for path in paths {
+        let path = match path {
+            Ok(path) => path,
+            Err(err) => { return Err(EvalError::IO(IOError::new(err.error().kind(), err.to_string()))); },
+        };
+        if !path.is_file() {
+            continue;
+        }
+        let path = match path.canonicalize() {
+            Ok(path) => path,
+            Err(err) => { return Err(EvalError::IO(err)); },
+        };
+        match path.strip_prefix(&src_path) {
+            Ok(file) => files.push(file.to_path_buf()),
+            Err(_) => {
+                let message = format!("matched {} outside of {}", path.display(), src_path.display());
+                return Err(EvalError::InvalidGlob(pattern.to_string(), message));
+            },
+        }
+    }
+    files.sort();
+
+    let mut outputs = Vec::new();
+    for file in files.iter() {
+        if let Some(out_dir) = out_path.join(file).parent() {
+            if let Err(err) = create_dir_all(out_dir) {
+                return Err(EvalError::IO(err));
+            }
+        }
+
+        let file = match file.to_str() {
+            Some(file) => file,
+            None => { return Err(not_unicode(pattern, file)); },
+        };
+        match build_path(file, &src_path, out_path, context) {
+            Ok(output) => outputs.push(output),
+            Err(err) => { return Err(err); },
+        }
+    }
+    Ok(Output::List(outputs))
+}
+
+/// The error for a glob pattern that leads to a path which is not valid unicode.
+fn not_unicode(pattern: &str, path: &Path) -> EvalError {
+    let message = format!("{} is not valid unicode", path.display());
+    EvalError::InvalidGlob(pattern.to_string(), message)
 }
 
-fn build_path(file: &str, src_path: &Path, out_path: &Path) -> EvalResult {
+/// Returns `true` if the file is built into an html page instead of being copied.
+pub fn is_page(file: &Path) -> bool {
+    Some(OsStr::new("foil")) == file.extension() || is_markdown_file(file)
+}
+
+/// Builds or copies the file `file` relative to `src_path` into `out_path` and returns the path
+/// of the output file relative to `out_path`. Unlike `evaluate_path` the path is never treated
+/// as a glob pattern.
+///
+/// # Errors
+/// `EvalError::NotFile` if `file` is not a file or doesn't exist relative to `src_path`.
+/// `EvalError::PathNotRelative` if `file` is an absolute path.
+pub fn build_path(file: &str, src_path: &Path, out_path: &Path, context: &Rc<Context>) -> EvalResult {
     // Allow only relative paths
     let in_file_path = Path::new(file);
     if in_file_path.is_absolute() {
@@ -39,26 +145,66 @@ fn build_path(file: &str, src_path: &Path, out_path: &Path) -> EvalResult {
     if !file_path.is_file() {
         let full_path = src_path.join(file);
         let full_path = full_path.to_str().unwrap();
-        return Err(EvalError::NotFile(full_path.to_string()));
+        return Err(EvalError::NotFile(vec![full_path.to_string()]));
     }
 
-    // if extension is "foil" then build the file
+    // if extension is "foil" or "md" then build the file
     // and change the extension to html.
-    if Some(OsStr::new("foil")) == file_path.extension() {
-        let out_file_path = out_file_path.with_extension("html");
-        evaluate_file(&file_path, &out_file_path)
-            .and_then(Output::to_string)
-            .and_then(|text| { 
-                write_to_file(&text, &out_file_path) 
-            })
-            .map(|_| { 
-                let out_file_rel_path = in_file_path.with_extension("html")
-                                                    .to_str()
-                                                    .unwrap_or("None")
-                                                    .to_string();
-                Output::String(out_file_rel_path)
-            })
+    if is_page(&file_path) {
+        build_page(&in_file_path, out_path, |out_dir| {
+            evaluate_file_with_context(&file_path, out_dir, context.clone())
+        })
     } else {
-        copy_file(&file_path, &out_file_path)
+        copy_file(&file_path, &out_file_path).map(|_| Output::String(file.to_string()))
+    }
+}
+
+/// Builds the `.foil` or `.md` file `in_file_path` into an html page in `out_path` and returns
+/// the path of the page relative to `out_path`.
+///
+/// # Arguments
+/// `in_file_path` - the relative path to the file.
+/// `out_path` - the output directory.
+/// `evaluate` - evaluates the file for the output directory it is given.
+pub fn build_page<F>(in_file_path: &Path, out_path: &Path, evaluate: F) -> EvalResult
+    where F: FnOnce(&Path) -> EvalResult {
+    let out_file_path = out_path.join(in_file_path).with_extension("html");
+    if is_markdown_file(in_file_path) {
+        let out_dir = out_file_path.parent().unwrap_or(out_path);
+        let output = evaluate(out_dir).and_then(markdown_content);
+        write_page(output, in_file_path, &out_file_path)
+    } else {
+        let output = evaluate(&out_file_path);
+        write_page(output, in_file_path, &out_file_path)
+    }
+}
+
+/// Writes the output of a built file to `out_file_path` and returns the path of the written file
+/// relative to the output directory.
+fn write_page(output: EvalResult, in_file_path: &Path, out_file_path: &Path) -> EvalResult {
+    output
+        .and_then(Output::to_string)
+        .and_then(|text| { 
+            write_to_file(&text, out_file_path) 
+        })
+        .map(|_| { 
+            let out_file_rel_path = in_file_path.with_extension("html")
+                                                .to_str()
+                                                .unwrap_or("None")
+                                                .to_string();
+            Output::String(out_file_rel_path)
+        })
+}
+
+/// Returns the rendered html of an evaluated markdown file. Outputs without the html can't be
+/// written as a page.
+fn markdown_content(output: Output) -> EvalResult {
+    let content = match output {
+        Output::Set(ref fields) => fields.get(CONTENT_FIELD).cloned(),
+        _ => None,
+    };
+    match content {
+        Some(content) => content.eval(),
+        None => Err(EvalError::NotStringable(output)),
     }
 }
@@ -0,0 +1,25 @@
+use compiler::models::{SetField, Output, Thunk};
+use compiler::evaluator::{Evaluator, EvalResult};
+use std::collections::{BTreeMap, HashSet};
+
+/// Evaluates a set literal. The fields are not evaluated here, they are stored as thunks in the
+/// environment of the set and evaluated when they are accessed. Only the variables that the
+/// fields use are captured.
+pub fn evaluate_set<'ast>(eval: &Evaluator<'ast>, fields: &Vec<SetField>) -> EvalResult {
+    let mut free = HashSet::new();
+    for field in fields.iter() {
+        free.extend(field.value.free_variables());
+    }
+
+    let env = eval.env.capture(&free);
+    let mut map = BTreeMap::new();
+    for field in fields.iter() {
+        let thunk = Thunk::new(field.value.clone(), 
+                               env.clone(), 
+                               eval.file_path.clone(), 
+                               eval.out_path.clone(),
+                               eval.context.clone());
+        map.insert(field.name.clone(), thunk);
+    }
+    Ok(Output::Set(map))
+}
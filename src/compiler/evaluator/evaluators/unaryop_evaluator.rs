@@ -0,0 +1,17 @@
+use compiler::models::{Ast, UnaryOp, Output};
+use compiler::evaluator::{Evaluator, EvalResult};
+use compiler::errors::EvalError;
+
+pub fn evaluate_unaryop<'ast>(eval: &Evaluator<'ast>, op: &'ast UnaryOp, expr: &'ast Ast) -> EvalResult {
+    match op {
+        &UnaryOp::Not => eval_not(eval, expr),
+    }
+}
+
+fn eval_not<'ast>(eval: &Evaluator<'ast>, expr: &Ast) -> EvalResult {
+    match eval.copy_for_expr(expr).eval() {
+        Ok(Output::Bool(b)) => Ok(Output::Bool(!b)),
+        Ok(not_bool) => Err(EvalError::NotABool(not_bool)),
+        Err(err) => Err(err),
+    }
+}
@@ -1,8 +1,13 @@
 mod evaluators;
+pub use self::evaluators::{evaluate_path, build_path, build_page, is_page};
+mod builtins;
 mod evaluator;
-pub use self::evaluator::{Evaluator, EvalResult};
+pub use self::evaluator::{Evaluator, EvalResult, Step};
 
-mod scope;
-pub use self::scope::{Scope, OpenScope, ClosedScope};
+mod env;
+pub use self::env::Env;
+
+mod context;
+pub use self::context::{Context, StackFrame, DepthGuard, FrameGuard, FileGuard, DEFAULT_MAX_DEPTH};
 
 #[cfg(test)] mod tests;
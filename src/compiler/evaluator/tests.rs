@@ -1,10 +1,14 @@
 use tempdir::TempDir;
-use std::io::{Write};
-use std::fs::{File, create_dir_all};
+use std::io::{Read, Write};
+use std::fs::{File, create_dir_all, remove_file};
+use std::collections::{HashMap, BTreeMap};
+use std::rc::Rc;
 
-use super::{Evaluator, OpenScope, Scope};
+use super::{Evaluator, EvalResult, Env, Context};
 use compiler::models::*;
 use compiler::errors::EvalError;
+use compiler::{evaluate_file, evaluate_file_with_context};
+use std::path::{Path, PathBuf};
 
 #[test]
 fn test_execute_binary_op() {
@@ -12,14 +16,13 @@ fn test_execute_binary_op() {
 
     let input = Ast::BinOp(
         BinOp::Add, 
-        Box::new(Ast::Val(Val::Int(3))),
-        Box::new(Ast::Val(Val::Int(4)))
+        Rc::new(Ast::Val(Val::Int(3))),
+        Rc::new(Ast::Val(Val::Int(4)))
         );
 
     let expected = Ok(Output::Int(7));
 
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&input, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
     assert_eq!(expected, actual);
 }
 
@@ -29,14 +32,13 @@ fn test_execute_recursive() {
     // = (1 - 2) + 3 = (-1) + 3 = 2
     let input = Ast::BinOp(
         BinOp::Add,
-        Box::new(Ast::BinOp(
+        Rc::new(Ast::BinOp(
                 BinOp::Sub,
-                Box::new(Ast::Val(Val::Int(1))),
-                Box::new(Ast::Val(Val::Int(2))))),
-                Box::new(Ast::Val(Val::Int(3))));
+                Rc::new(Ast::Val(Val::Int(1))),
+                Rc::new(Ast::Val(Val::Int(2))))),
+                Rc::new(Ast::Val(Val::Int(3))));
     let expected = Ok(Output::Int(2));
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&input, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
     assert_eq!(expected, actual);
 }
 
@@ -45,19 +47,18 @@ fn test_execute_let_statement() {
     // let x = 2 in + x 1
     let input = 
         Ast::Let(
-            Box::new(
+            vec![
                 SetField {
                     name: "x".to_string(),
-                    value: Ast::Val(Val::Int(2))
-                }),
-                Box::new(Ast::BinOp(
+                    value: Rc::new(Ast::Val(Val::Int(2)))
+                }],
+                Rc::new(Ast::BinOp(
                         BinOp::Add,
-                        Box::new(Ast::Id(Id(0, "x".to_string()))),
-                        Box::new(Ast::Val(Val::Int(1))))));
+                        Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                        Rc::new(Ast::Val(Val::Int(1))))));
 
     let expected = Ok(Output::Int(3));
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&input, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
     assert_eq!(expected, actual);
 }
 
@@ -67,27 +68,26 @@ fn test_nested_let() {
     
     let inner = 
         Ast::Let(
-            Box::new(
+            vec![
                 SetField {
                     name: "y".to_string(),
-                    value: Ast::Val(Val::Int(2))
-                }),
-                Box::new(Ast::BinOp(
+                    value: Rc::new(Ast::Val(Val::Int(2)))
+                }],
+                Rc::new(Ast::BinOp(
                         BinOp::Add,
-                        Box::new(Ast::Id(Id(0, "x".to_string()))),
-                        Box::new(Ast::Id(Id(0, "y".to_string()))))));
+                        Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                        Rc::new(Ast::Id(Id(0, "y".to_string()))))));
     let input = 
         Ast::Let(
-            Box::new(
+            vec![
                 SetField {
                     name: "x".to_string(),
-                    value: Ast::Val(Val::Int(1))
-                }),
-                Box::new(inner));
+                    value: Rc::new(Ast::Val(Val::Int(1)))
+                }],
+                Rc::new(inner));
 
     let expected = Ok(Output::Int(3));
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&input, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
     assert_eq!(expected, actual);
 }
 
@@ -98,27 +98,26 @@ fn test_shadowing_works() {
     
     let inner = 
         Ast::Let(
-            Box::new(
+            vec![
                 SetField {
                     name: "x".to_string(),
-                    value: Ast::Val(Val::Int(2))
-                }),
-                Box::new(Ast::BinOp(
+                    value: Rc::new(Ast::Val(Val::Int(2)))
+                }],
+                Rc::new(Ast::BinOp(
                         BinOp::Add,
-                        Box::new(Ast::Id(Id(0, "x".to_string()))),
-                        Box::new(Ast::Val(Val::Int(1))))));
+                        Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                        Rc::new(Ast::Val(Val::Int(1))))));
     let input = 
         Ast::Let(
-            Box::new(
+            vec![
                 SetField {
                     name: "x".to_string(),
-                    value: Ast::Val(Val::Int(1))
-                }),
-                Box::new(inner));
+                    value: Rc::new(Ast::Val(Val::Int(1)))
+                }],
+                Rc::new(inner));
 
     let expected = Ok(Output::Int(3));
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&input, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
     assert_eq!(expected, actual);
 }
 
@@ -127,17 +126,16 @@ fn test_shadowing_works() {
 fn test_function_call() {
     // (fn x: + x 1 2)
     let binop = Ast::BinOp(BinOp::Add,
-                           Box::new(Ast::Id(Id(9, "x".to_string()))),
-                           Box::new(Ast::Val(Val::Int(1))));
-    let func = Ast::Fn("x".to_string(), Box::new(binop));
+                           Rc::new(Ast::Id(Id(9, "x".to_string()))),
+                           Rc::new(Ast::Val(Val::Int(1))));
+    let func = Ast::Fn("x".to_string(), Rc::new(binop));
     let fncall = 
         Ast::Call(
-            Box::new(func),
-            Box::new( Ast::Val(Val::Int(2))));
+            Rc::new(func),
+            vec![Arg(0, Rc::new( Ast::Val(Val::Int(2))))]);
 
     let expected = Ok(Output::Int(3));
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&fncall, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&fncall, Env::new()).eval();
     assert_eq!(expected, actual);
 }
 
@@ -148,29 +146,28 @@ fn closure_works() {
     //   (func 1)
     
     let fncall = Ast::Call(
-        Box::new(Ast::Id(Id(0, "func".to_string()))),
-        Box::new(Ast::Val(Val::Int(1))));
+        Rc::new(Ast::Id(Id(0, "func".to_string()))),
+        vec![Arg(0, Rc::new(Ast::Val(Val::Int(1))))]);
     let func = Ast::Fn("y".to_string(), 
-                       Box::new(Ast::BinOp(
+                       Rc::new(Ast::BinOp(
                                BinOp::Add,
-                               Box::new(Ast::Id(Id(0, "y".to_string()))),
-                               Box::new(Ast::Id(Id(0, "x".to_string()))))));
+                               Rc::new(Ast::Id(Id(0, "y".to_string()))),
+                               Rc::new(Ast::Id(Id(0, "x".to_string()))))));
     let inner_let = Ast::Let(
-        Box::new(SetField{name: "func".to_string(), value: func}),
-        Box::new(fncall));
+        vec![SetField{name: "func".to_string(), value: Rc::new(func)}],
+        Rc::new(fncall));
 
 
     let outer_let = Ast::Let(
-        Box::new(SetField{
+        vec![SetField{
             name: "x".to_string(),
-            value: Ast::Val(Val::Int(2))
-        }),
-        Box::new(inner_let));
+            value: Rc::new(Ast::Val(Val::Int(2)))
+        }],
+        Rc::new(inner_let));
 
 
     let expected = Ok(Output::Int(3));
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&outer_let, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&outer_let, Env::new()).eval();
     assert_eq!(expected, actual);
 }
 
@@ -181,32 +178,293 @@ fn shadowing_in_closure_works() {
     //   (func 1)
     
     let fncall = Ast::Call(
-        Box::new(Ast::Id(Id(0, "func".to_string()))),
-        Box::new(Ast::Val(Val::Int(1))));
+        Rc::new(Ast::Id(Id(0, "func".to_string()))),
+        vec![Arg(0, Rc::new(Ast::Val(Val::Int(1))))]);
     let func = Ast::Fn("x".to_string(), 
-                       Box::new(Ast::BinOp(
+                       Rc::new(Ast::BinOp(
                                BinOp::Add,
-                               Box::new(Ast::Id(Id(0, "x".to_string()))),
-                               Box::new(Ast::Val(Val::Int(5))))));
+                               Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                               Rc::new(Ast::Val(Val::Int(5))))));
     let inner_let = Ast::Let(
-        Box::new(SetField{name: "func".to_string(), value: func}),
-        Box::new(fncall));
+        vec![SetField{name: "func".to_string(), value: Rc::new(func)}],
+        Rc::new(fncall));
 
 
     let outer_let = Ast::Let(
-        Box::new(SetField{
+        vec![SetField{
             name: "x".to_string(),
-            value: Ast::Val(Val::Int(100))
-        }),
-        Box::new(inner_let));
+            value: Rc::new(Ast::Val(Val::Int(100)))
+        }],
+        Rc::new(inner_let));
 
 
     let expected = Ok(Output::Int(6));
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&outer_let, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&outer_let, Env::new()).eval();
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn call_argument_is_evaluated_in_callers_scope() {
+    // let f = fn y: y in
+    //  let x = 5 in
+    //   (f x)
+    let fncall = Ast::Call(
+        Rc::new(Ast::Id(Id(0, "f".to_string()))),
+        vec![Arg(0, Rc::new(Ast::Id(Id(0, "x".to_string()))))]);
+    let inner_let = Ast::Let(
+        vec![SetField{name: "x".to_string(), value: Rc::new(Ast::Val(Val::Int(5)))}],
+        Rc::new(fncall));
+    let func = Ast::Fn("y".to_string(), Rc::new(Ast::Id(Id(0, "y".to_string()))));
+    let outer_let = Ast::Let(
+        vec![SetField{name: "f".to_string(), value: Rc::new(func)}],
+        Rc::new(inner_let));
+
+    let expected = Ok(Output::Int(5));
+    let actual = Evaluator::without_files(&outer_let, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn call_argument_is_not_shadowed_by_closure_scope() {
+    // let x = 1 in
+    //  let f = fn y: + x y in
+    //   let x = 10 in
+    //    (f x)
+    let fncall = Ast::Call(
+        Rc::new(Ast::Id(Id(0, "f".to_string()))),
+        vec![Arg(0, Rc::new(Ast::Id(Id(0, "x".to_string()))))]);
+    let inner_let = Ast::Let(
+        vec![SetField{name: "x".to_string(), value: Rc::new(Ast::Val(Val::Int(10)))}],
+        Rc::new(fncall));
+    let func = Ast::Fn("y".to_string(),
+                       Rc::new(Ast::BinOp(
+                               BinOp::Add,
+                               Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                               Rc::new(Ast::Id(Id(0, "y".to_string()))))));
+    let middle_let = Ast::Let(
+        vec![SetField{name: "f".to_string(), value: Rc::new(func)}],
+        Rc::new(inner_let));
+    let outer_let = Ast::Let(
+        vec![SetField{name: "x".to_string(), value: Rc::new(Ast::Val(Val::Int(1)))}],
+        Rc::new(middle_let));
+
+    let expected = Ok(Output::Int(11));
+    let actual = Evaluator::without_files(&outer_let, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn higher_order_functions_get_arguments_from_callers_scope() {
+    // let apply = fn g: fn v: (g v) in
+    //  let x = 3 in
+    //   ((apply fn y: + y 1) x)
+    let apply = Ast::Fn("g".to_string(),
+                        Rc::new(Ast::Fn("v".to_string(),
+                                         Rc::new(Ast::Call(
+                                                 Rc::new(Ast::Id(Id(0, "g".to_string()))),
+                                                 vec![Arg(0, Rc::new(Ast::Id(Id(0, "v".to_string()))))])))));
+    let increment = Ast::Fn("y".to_string(),
+                            Rc::new(Ast::BinOp(
+                                    BinOp::Add,
+                                    Rc::new(Ast::Id(Id(0, "y".to_string()))),
+                                    Rc::new(Ast::Val(Val::Int(1))))));
+    let fncall = Ast::Call(
+        Rc::new(Ast::Call(
+                Rc::new(Ast::Id(Id(0, "apply".to_string()))),
+                vec![Arg(0, Rc::new(increment))])),
+        vec![Arg(0, Rc::new(Ast::Id(Id(0, "x".to_string()))))]);
+    let inner_let = Ast::Let(
+        vec![SetField{name: "x".to_string(), value: Rc::new(Ast::Val(Val::Int(3)))}],
+        Rc::new(fncall));
+    let outer_let = Ast::Let(
+        vec![SetField{name: "apply".to_string(), value: Rc::new(apply)}],
+        Rc::new(inner_let));
+
+    let expected = Ok(Output::Int(4));
+    let actual = Evaluator::without_files(&outer_let, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn unused_call_argument_is_not_evaluated() {
+    // (fn y: 1 undefined)
+    let fncall = Ast::Call(
+        Rc::new(Ast::Fn("y".to_string(), Rc::new(Ast::Val(Val::Int(1))))),
+        vec![Arg(0, Rc::new(Ast::Id(Id(0, "undefined".to_string()))))]);
+
+    let expected = Ok(Output::Int(1));
+    let actual = Evaluator::without_files(&fncall, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn call_argument_keeps_the_callers_file_paths() {
+    // (fn p: p <doesnt_exist.foil>)
+    let tmp_working_dir = TempDir::new("src").unwrap();
+    let tmp_working_dir = tmp_working_dir.path().to_path_buf();
+    let tmp_out_dir = TempDir::new("out").unwrap();
+    let tmp_out_dir = tmp_out_dir.path().to_path_buf();
+
+    let file_name = "doesnt_exist.foil";
+    let fncall = Ast::Call(
+        Rc::new(Ast::Fn("p".to_string(), Rc::new(Ast::Id(Id(0, "p".to_string()))))),
+        vec![Arg(0, Rc::new(Ast::Val(Val::Path(file_name.to_string()))))]);
+
+    // Without the file paths evaluation would fail with `OutputPathNotSpecified`.
+    let full_path = tmp_working_dir.join(file_name);
+    let expected = Err(EvalError::NotFile(vec![full_path.to_str().unwrap().to_string()]));
+    let actual = Evaluator::new(&fncall, Env::new(), tmp_working_dir.join("file.foil"), tmp_out_dir).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn recursive_functions_do_not_keep_themselves_alive() {
+    // let f = fn x: (f x) in f
+    let context = Rc::new(Context::default());
+    let weak_context = Rc::downgrade(&context);
+    let func = Ast::Fn("x".to_string(), Rc::new(Ast::Call( 
+                                                          Rc::new(Ast::Id(Id(0, "f".to_string()))),
+                                                          vec![Arg(0, Rc::new(Ast::Id(Id(0, "x".to_string()))))])));
+    let input = Ast::Let(vec![SetField{name: "f".to_string(), value: Rc::new(func)}], 
+                         Rc::new(Ast::Id(Id(0, "f".to_string()))));
+
+    // The function refers back to its own definition and the definition to the context.
+    let func = Evaluator::with_context(&input, Env::new(), None, None, context).eval();
+    assert!(func.is_ok());
+    assert!(weak_context.upgrade().is_some());
+
+    drop(func);
+    assert!(weak_context.upgrade().is_none());
+}
+
+#[test]
+fn free_variables_exclude_parameters_and_let_bindings() {
+    // fn x: let y = z in + x + y w
+    let input = Ast::Fn("x".to_string(),
+                        Rc::new(Ast::Let(
+                                vec![SetField{
+                                    name: "y".to_string(),
+                                    value: Rc::new(Ast::Id(Id(0, "z".to_string())))
+                                }],
+                                Rc::new(Ast::BinOp(
+                                        BinOp::Add,
+                                        Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                                        Rc::new(Ast::BinOp(
+                                                BinOp::Add,
+                                                Rc::new(Ast::Id(Id(0, "y".to_string()))),
+                                                Rc::new(Ast::Id(Id(0, "w".to_string()))))))))));
+
+    let mut actual: Vec<_> = input.free_variables().into_iter().collect();
+    actual.sort();
+    assert_eq!(vec!["w".to_string(), "z".to_string()], actual);
+}
+
+#[test]
+fn closures_only_capture_the_variables_they_use() {
+    // let used = 1, unused = 2 in fn x: + x used
+    let func = Ast::Fn("x".to_string(), Rc::new(Ast::BinOp(
+                BinOp::Add,
+                Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                Rc::new(Ast::Id(Id(0, "used".to_string()))))));
+    let input = Ast::Let(
+        vec![SetField{name: "used".to_string(), value: Rc::new(Ast::Val(Val::Int(1)))},
+             SetField{name: "unused".to_string(), value: Rc::new(Ast::Val(Val::Int(2)))}],
+        Rc::new(func));
+
+    match Evaluator::without_files(&input, Env::new()).eval() {
+        Ok(Output::Fn(closure)) => {
+            assert!(format!("{:?}", closure).contains("env: [[\"used\"]]"));
+            assert_eq!(Ok(Output::Int(3)), closure.apply(Output::Int(2)));
+        },
+        other => panic!("Expected a function but got {:?}", other),
+    }
+}
+
+#[test]
+fn creating_a_closure_does_not_evaluate_unused_bindings() {
+    // let asset = <style.css> in (fn x: x 1)
+    let tmpdir = TempDir::new("src").unwrap();
+    File::create(tmpdir.path().join("style.css")).unwrap();
+    let outdir = TempDir::new("out").unwrap();
+
+    let fncall = Ast::Call(
+        Rc::new(Ast::Fn("x".to_string(), Rc::new(Ast::Id(Id(0, "x".to_string()))))),
+        vec![Arg(0, Rc::new(Ast::Val(Val::Int(1))))]);
+    let input = Ast::Let(
+        vec![SetField{name: "asset".to_string(), value: Rc::new(Ast::Val(Val::Path("style.css".to_string())))}],
+        Rc::new(fncall));
+
+    let actual = Evaluator::new(&input, Env::new(), 
+                                tmpdir.path().join("index.foil"), 
+                                outdir.path().to_path_buf()).eval();
+    assert_eq!(Ok(Output::Int(1)), actual);
+    assert!(!outdir.path().join("style.css").exists());
+}
+
+#[test]
+fn variables_are_evaluated_at_most_once() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let css_file = tmpdir.path().join("style.css");
+    File::create(&css_file).unwrap();
+    let outdir = TempDir::new("out").unwrap();
+
+    let css = Thunk::new(Rc::new(Ast::Val(Val::Path("style.css".to_string()))), 
+                         Env::new(), 
+                         Some(tmpdir.path().join("index.foil")), 
+                         Some(outdir.path().to_path_buf()),
+                         Rc::new(Context::default()));
+    let mut vars = HashMap::new();
+    vars.insert("css".to_string(), css);
+    let env = Env::new().with_vars(vars);
+
+    let first = env.get_value("css");
+    assert!(first.as_ref().unwrap().is_ok());
+
+    // The file would not be found if the path was evaluated again.
+    remove_file(&css_file).unwrap();
+    assert_eq!(first, env.get_value("css"));
+}
+
+#[test]
+fn thunks_are_evaluated_at_most_once() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let css_file = tmpdir.path().join("style.css");
+    File::create(&css_file).unwrap();
+    let outdir = TempDir::new("out").unwrap();
+
+    let thunk = Thunk::new(Rc::new(Ast::Val(Val::Path("style.css".to_string()))), 
+                           Env::new(), 
+                           Some(tmpdir.path().join("index.foil")), 
+                           Some(outdir.path().to_path_buf()),
+                           Rc::new(Context::default()));
+    let first = thunk.eval();
+    assert!(first.is_ok());
+
+    // The file would not be found if the path was evaluated again.
+    remove_file(&css_file).unwrap();
+    assert_eq!(first, thunk.clone().eval());
+}
+
+#[test]
+fn closures_outlive_their_ast() {
+    // let y = 2 in fn x: + x y
+    let func = {
+        let input = Ast::Let(
+            vec![SetField{name: "y".to_string(), value: Rc::new(Ast::Val(Val::Int(2)))}],
+            Rc::new(Ast::Fn("x".to_string(),
+                             Rc::new(Ast::BinOp(
+                                     BinOp::Add,
+                                     Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                                     Rc::new(Ast::Id(Id(0, "y".to_string()))))))));
+        Evaluator::without_files(&input, Env::new()).eval()
+    };
+
+    match func {
+        Ok(Output::Fn(func)) => assert_eq!(Ok(Output::Int(3)), func.apply(Output::Int(1))),
+        other => panic!("Expected a function but got {:?}", other),
+    }
+}
+
 #[test]
 fn import_works() {
     let tmpdir = TempDir::new("test").unwrap();
@@ -226,11 +484,170 @@ fn import_works() {
 
     let input = Ast::Import(0, import_file.to_str().unwrap().to_string());
     let expected = Ok(Output::Int(3));
-    let scope = OpenScope::new();
-    let actual = Evaluator::new(&input, Scope::Open(&scope), import_file.to_path_buf(), out_file.to_path_buf()).eval();
+    let actual = Evaluator::new(&input, Env::new(), import_file.to_path_buf(), out_file.to_path_buf()).eval();
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn imported_closures_resolve_paths_relative_to_their_file() {
+    // (import "lib/layout.foil" "content")
+    let tmpdir = TempDir::new("src").unwrap();
+    let lib_dir = tmpdir.path().join("lib");
+    create_dir_all(&lib_dir).unwrap();
+
+    let contents = "fn content: html! div { link href=<style.css>; (content) }".as_bytes();
+    let layout_file = lib_dir.join("layout.foil");
+    {
+        let mut f = File::create(&layout_file).unwrap();
+        f.write_all(contents).unwrap();
+        f.sync_all().unwrap();
+    }
+    File::create(lib_dir.join("style.css")).unwrap();
+
+    let outdir = TempDir::new("out").unwrap();
+
+    let input = Ast::Call(
+        Rc::new(Ast::Import(0, "lib/layout.foil".to_string())),
+        vec![Arg(0, Rc::new(Ast::Val(Val::String("content".to_string()))))]);
+    let actual = Evaluator::new(&input, Env::new(), 
+                                tmpdir.path().join("index.foil"), 
+                                outdir.path().to_path_buf()).eval();
+    assert!(actual.is_ok());
+    assert!(outdir.path().join("style.css").is_file());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn RecursionLimit_should_contain_files() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let import_file = tmpdir.path().join("recursion.foil");
+    {
+        let mut f = File::create(&import_file).unwrap();
+        f.write_all("let f = fn x: + 1 (f x) in (f 1)".as_bytes()).unwrap();
+        f.sync_all().unwrap();
+    }
+    let outdir = TempDir::new("out").unwrap();
+
+    let context = Rc::new(Context::new(20));
+    match evaluate_file_with_context(&import_file, outdir.path(), context) {
+        Err(EvalError::RecursionLimit(frames)) => {
+            assert_eq!(import_file.display().to_string(), frames[0]);
+            assert_eq!("f at 30", frames[1]);
+        },
+        other => panic!("Expected RecursionLimit but got {:?}", other),
+    }
+}
+
+/// Creates the file `name` in `dir` with the given contents and returns its canonical path.
+fn create_file(dir: &Path, name: &str, contents: &str) -> PathBuf {
+    let path = dir.join(name);
+    let mut f = File::create(&path).unwrap();
+    f.write_all(contents.as_bytes()).unwrap();
+    f.sync_all().unwrap();
+    path.canonicalize().unwrap()
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn import_cycle_should_return_ImportCycle() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let a = create_file(tmpdir.path(), "a.foil", "import \"b.foil\"");
+    let b = create_file(tmpdir.path(), "b.foil", "import \"a.foil\"");
+    let outdir = TempDir::new("out").unwrap();
+
+    let expected = Err(EvalError::ImportCycle(vec![a.clone(), b, a.clone()]));
+    assert_eq!(expected, evaluate_file(&a, outdir.path()));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn path_cycle_should_return_ImportCycle() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let index = create_file(tmpdir.path(), "index.foil", "html! a href=<index.foil>;");
+    let outdir = TempDir::new("out").unwrap();
+
+    let expected = Err(EvalError::ImportCycle(vec![index.clone(), index.clone()]));
+    assert_eq!(expected, evaluate_file(&index, outdir.path()));
+}
+
+#[test]
+fn importing_a_file_twice_is_not_a_cycle() {
+    let tmpdir = TempDir::new("src").unwrap();
+    create_file(tmpdir.path(), "one.foil", "1");
+    let index = create_file(tmpdir.path(), "index.foil", "+ import \"one.foil\" import \"one.foil\"");
+    let outdir = TempDir::new("out").unwrap();
+
+    assert_eq!(Ok(Output::Int(2)), evaluate_file(&index, outdir.path()));
+}
+
+#[test]
+fn imported_files_are_evaluated_once_per_build() {
+    let tmpdir = TempDir::new("src").unwrap();
+    create_file(tmpdir.path(), "one.foil", "1");
+    let index = create_file(tmpdir.path(), "index.foil", "+ import \"one.foil\" import \"one.foil\"");
+    let outdir = TempDir::new("out").unwrap();
+    let context = Rc::new(Context::default());
+
+    let actual = evaluate_file_with_context(&index, outdir.path(), context.clone());
+    assert_eq!(Ok(Output::Int(2)), actual);
+    assert_eq!(1, context.cache_hits());
+    assert_eq!(2, context.cache_misses());
+}
+
+#[test]
+fn cached_modules_are_not_read_again() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let one = create_file(tmpdir.path(), "one.foil", "1");
+    let outdir = TempDir::new("out").unwrap();
+    let context = Rc::new(Context::default());
+
+    let first = evaluate_file_with_context(&one, outdir.path(), context.clone());
+    create_file(tmpdir.path(), "one.foil", "2");
+    let second = evaluate_file_with_context(&one, outdir.path(), context.clone());
+
+    assert_eq!(Ok(Output::Int(1)), first);
+    assert_eq!(Ok(Output::Int(1)), second);
+    assert_eq!(Ok(Output::Int(2)), evaluate_file(&one, outdir.path()));
+}
+
+#[test]
+fn clearing_the_module_cache_frees_the_context() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let lib = create_file(tmpdir.path(), "lib.foil", "fn x: x");
+    let outdir = TempDir::new("out").unwrap();
+    let context = Rc::new(Context::default());
+    let weak_context = Rc::downgrade(&context);
+
+    // The cached function refers back to the context.
+    assert!(evaluate_file_with_context(&lib, outdir.path(), context.clone()).is_ok());
+    context.clear_modules();
+    drop(context);
+    assert!(weak_context.upgrade().is_none());
+}
+
+#[test]
+fn functions_returned_from_a_file_can_be_called() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let input = "
+        let sum = fn n: if == n 0 then 0 else + n (sum - n 1),
+            step = 2,
+            sum_steps = fn n: if <= n 0 then 0 else + n (sum_steps - n step)
+        in set { sum = sum, sum_steps = sum_steps }";
+    let lib = create_file(tmpdir.path(), "lib.foil", input);
+    let outdir = TempDir::new("out").unwrap();
+
+    let fields = match evaluate_file(&lib, outdir.path()) {
+        Ok(Output::Set(fields)) => fields,
+        other => panic!("Expected a set but got {:?}", other),
+    };
+    for &(name, expected) in [("sum", 6), ("sum_steps", 4)].iter() {
+        match fields[name].eval() {
+            Ok(Output::Fn(func)) => assert_eq!(Ok(Output::Int(expected)), func.apply(Output::Int(3))),
+            other => panic!("Expected a function but got {:?}", other),
+        }
+    }
+}
+
 #[test]
 #[allow(non_snake_case)]
 fn import_should_return_NotFile_error_for_non_existing_paths() {
@@ -249,13 +666,397 @@ fn import_should_return_NotFile_error_for_non_existing_paths() {
 
     // Prepare expected
     let full_path = tmp_working_dir.join(file_name);
-    let expected = Err(EvalError::NotFile(full_path.to_str().unwrap().to_string()));
+    let expected = Err(EvalError::NotFile(vec![full_path.to_str().unwrap().to_string()]));
     
     // Prepare actual result
-    let scope = OpenScope::new();
-    let actual = Evaluator::new(&import_expr, Scope::Open(&scope), tmp_working_dir.join("file.foil"), tmp_out_dir).eval();
+    let actual = Evaluator::new(&import_expr, Env::new(), tmp_working_dir.join("file.foil"), tmp_out_dir).eval();
+
+    assert_eq!(expected, actual);
+}
+
+/// Evaluates `index.foil` in `src` with the given project root and search path.
+fn evaluate_with_search_path(src: &Path, root: Option<PathBuf>, search_path: Vec<PathBuf>) -> EvalResult {
+    let outdir = TempDir::new("out").unwrap();
+    let mut context = Context::default();
+    context.root = root;
+    context.search_path = search_path;
+    evaluate_file_with_context(&src.join("index.foil"), outdir.path(), Rc::new(context))
+}
+
+#[test]
+fn import_should_search_the_search_path() {
+    let src = TempDir::new("src").unwrap();
+    let lib1 = TempDir::new("lib1").unwrap();
+    let lib2 = TempDir::new("lib2").unwrap();
+    create_file(src.path(), "index.foil", "import \"nav.foil\"");
+    create_file(lib1.path(), "nav.foil", "1");
+    create_file(lib2.path(), "nav.foil", "2");
+
+    let search_path = vec![lib1.path().to_path_buf(), lib2.path().to_path_buf()];
+    let actual = evaluate_with_search_path(src.path(), None, search_path);
+    assert_eq!(Ok(Output::Int(1)), actual);
+}
+
+#[test]
+fn import_should_prefer_files_next_to_the_importing_file() {
+    let src = TempDir::new("src").unwrap();
+    let lib = TempDir::new("lib").unwrap();
+    create_file(src.path(), "index.foil", "import \"nav.foil\"");
+    create_file(src.path(), "nav.foil", "1");
+    create_file(lib.path(), "nav.foil", "2");
+
+    let actual = evaluate_with_search_path(src.path(), None, vec![lib.path().to_path_buf()]);
+    assert_eq!(Ok(Output::Int(1)), actual);
+}
+
+#[test]
+fn root_relative_import_should_resolve_against_the_project_root() {
+    let root = TempDir::new("root").unwrap();
+    let pages = root.path().join("pages");
+    create_dir_all(&pages).unwrap();
+    create_dir_all(root.path().join("lib")).unwrap();
+    create_file(&pages, "index.foil", "import \"@/lib/nav.foil\"");
+    create_file(&root.path().join("lib"), "nav.foil", "1");
+
+    let actual = evaluate_with_search_path(&pages, Some(root.path().to_path_buf()), vec![]);
+    assert_eq!(Ok(Output::Int(1)), actual);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn import_should_return_NotFile_with_every_location_that_was_tried() {
+    let src = TempDir::new("src").unwrap();
+    let lib = TempDir::new("lib").unwrap();
+    create_file(src.path(), "index.foil", "import \"nav.foil\"");
+
+    let expected = Err(EvalError::NotFile(vec![
+        src.path().join("nav.foil").to_str().unwrap().to_string(),
+        lib.path().join("nav.foil").to_str().unwrap().to_string(),
+    ]));
+    let actual = evaluate_with_search_path(src.path(), None, vec![lib.path().to_path_buf()]);
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn json_imports_should_be_converted_to_values() {
+    let tmpdir = TempDir::new("src").unwrap();
+    create_file(tmpdir.path(), "team.json", r#"{"name": "Ada", "age": 36, "height": 1.7, "admin": true, "tags": ["a", null]}"#);
+    let index = create_file(tmpdir.path(), "index.foil", "import \"team.json\"");
+    let outdir = TempDir::new("out").unwrap();
+
+    let mut fields = BTreeMap::new();
+    fields.insert("name".to_string(), Thunk::evaluated(Output::String("Ada".to_string())));
+    fields.insert("age".to_string(), Thunk::evaluated(Output::Int(36)));
+    fields.insert("height".to_string(), Thunk::evaluated(Output::Double(1.7)));
+    fields.insert("admin".to_string(), Thunk::evaluated(Output::Bool(true)));
+    fields.insert("tags".to_string(), Thunk::evaluated(Output::List(vec![
+        Output::String("a".to_string()),
+        Output::List(vec![]),
+    ])));
+    assert_eq!(Ok(Output::Set(fields)), evaluate_file(&index, outdir.path()));
+}
 
+#[test]
+fn toml_imports_should_be_converted_to_values() {
+    let tmpdir = TempDir::new("src").unwrap();
+    create_file(tmpdir.path(), "site.toml", "title = \"Foil\"\n[[pages]]\nweight = 2\n");
+    let index = create_file(tmpdir.path(), "index.foil", "let site = import \"site.toml\" in site.title");
+    let outdir = TempDir::new("out").unwrap();
+
+    assert_eq!(Ok(Output::String("Foil".to_string())), evaluate_file(&index, outdir.path()));
+}
+
+#[test]
+fn csv_imports_should_be_converted_to_lists_of_sets() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let products = create_file(tmpdir.path(), "products.csv", "name,price\nfoil,10\nink,2\n");
+    let outdir = TempDir::new("out").unwrap();
+
+    let row = |name: &str, price: &str| {
+        let mut fields = BTreeMap::new();
+        fields.insert("name".to_string(), Thunk::evaluated(Output::String(name.to_string())));
+        fields.insert("price".to_string(), Thunk::evaluated(Output::String(price.to_string())));
+        Output::Set(fields)
+    };
+    let expected = Ok(Output::List(vec![row("foil", "10"), row("ink", "2")]));
+    assert_eq!(expected, evaluate_file(&products, outdir.path()));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn invalid_data_files_should_return_InvalidData_with_the_line() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let json = create_file(tmpdir.path(), "team.json", "{\n\"name\": \"Ada\",\n}");
+    let toml = create_file(tmpdir.path(), "site.toml", "title = \"Foil\"\nbroken\n");
+    let csv = create_file(tmpdir.path(), "products.csv", "name,price\nfoil,10\nink\n");
+    // The TOML parser doesn't report the position of duplicate keys.
+    let duplicate = create_file(tmpdir.path(), "duplicate.toml", "title = \"Foil\"\ntitle = \"Foil\"\n");
+    let post = create_file(tmpdir.path(), "post.md", "+++\ntitle = \"Foil\"\ntitle = \"Foil\"\n+++\n");
+    let outdir = TempDir::new("out").unwrap();
+
+    for &(ref file, line) in &[(json, Some(3)), (toml, Some(2)), (csv, Some(3)), (duplicate, None), (post, None)] {
+        match evaluate_file(file, outdir.path()) {
+            Err(EvalError::InvalidData(ref path, ref actual_line, _)) => {
+                assert_eq!(file, path);
+                assert_eq!(line, *actual_line);
+            },
+            res => panic!("Expected InvalidData for {:?}, got {:?}", file, res),
+        }
+    }
+}
+
+#[test]
+fn markdown_imports_should_render_html_and_front_matter() {
+    let tmpdir = TempDir::new("src").unwrap();
+    create_file(tmpdir.path(), "hello.md", "+++\ntitle = \"Hello\"\n+++\n# Hi\n\nSome *text*.\n");
+    let index = create_file(tmpdir.path(), "index.foil", "import \"hello.md\"");
+    let outdir = TempDir::new("out").unwrap();
+
+    let mut fields = BTreeMap::new();
+    fields.insert("title".to_string(), Thunk::evaluated(Output::String("Hello".to_string())));
+    fields.insert("content".to_string(), Thunk::evaluated(Output::String(
+        "<h1>Hi</h1>\n<p>Some <em>text</em>.</p>\n".to_string())));
+    assert_eq!(Ok(Output::Set(fields)), evaluate_file(&index, outdir.path()));
+}
+
+#[test]
+fn markdown_without_closing_front_matter_delimiter_should_render_the_delimiter() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let post = create_file(tmpdir.path(), "post.md", "+++");
+    let outdir = TempDir::new("out").unwrap();
+
+    let mut fields = BTreeMap::new();
+    fields.insert("content".to_string(), Thunk::evaluated(Output::String("<p>+++</p>\n".to_string())));
+    assert_eq!(Ok(Output::Set(fields)), evaluate_file(&post, outdir.path()));
+}
+
+#[test]
+fn markdown_links_should_be_built_into_the_out_directory() {
+    let tmpdir = TempDir::new("src").unwrap();
+    create_file(tmpdir.path(), "logo.png", "png");
+    create_file(tmpdir.path(), "about.foil", "\"about\"");
+    let post = create_file(tmpdir.path(), "post.md", "![logo](logo.png) [about](about.foil#team) [home](https://foil.stani.se)");
+    let outdir = TempDir::new("out").unwrap();
+
+    let expected = "<p><img src=\"logo.png\" alt=\"logo\" /> <a href=\"about.html#team\">about</a> \
+                    <a href=\"https://foil.stani.se\">home</a></p>\n";
+    let actual = evaluate_file(&post, outdir.path()).and_then(|post| match post {
+        Output::Set(fields) => fields["content"].eval(),
+        other => Ok(other),
+    });
+    assert_eq!(Ok(Output::String(expected.to_string())), actual);
+    assert!(outdir.path().join("logo.png").is_file());
+    assert!(outdir.path().join("about.html").is_file());
+}
+
+#[test]
+fn markdown_posts_can_link_to_each_other() {
+    let tmpdir = TempDir::new("src").unwrap();
+    create_file(tmpdir.path(), "a.md", "[b](b.md)");
+    create_file(tmpdir.path(), "b.md", "[a](a.md#top)");
+    let outdir = TempDir::new("out").unwrap();
+    let path_expr = Ast::Val(Val::Path("a.md".to_string()));
+
+    let actual = Evaluator::new(&path_expr, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
+    assert_eq!(Ok(Output::String("a.html".to_string())), actual);
+
+    let mut page = String::new();
+    File::open(outdir.path().join("a.html")).unwrap().read_to_string(&mut page).unwrap();
+    assert_eq!("<p><a href=\"b.html\">b</a></p>\n", page);
+    page.clear();
+    File::open(outdir.path().join("b.html")).unwrap().read_to_string(&mut page).unwrap();
+    assert_eq!("<p><a href=\"a.html#top\">a</a></p>\n", page);
+}
+
+#[test]
+fn markdown_links_to_other_targets_should_be_left_unchanged() {
+    let tmpdir = TempDir::new("src").unwrap();
+    create_dir_all(tmpdir.path().join("posts")).unwrap();
+    create_file(tmpdir.path(), "page.foil", "\"page\"");
+    create_file(tmpdir.path(), "what?.png", "png");
+    let post = create_file(tmpdir.path(), "post.md", "[about](about.html) [posts](posts/) [page](page.foil?x=1) ![what](what?.png)");
+    let outdir = TempDir::new("out").unwrap();
+
+    let expected = "<p><a href=\"about.html\">about</a> <a href=\"posts/\">posts</a> \
+                    <a href=\"page.html?x=1\">page</a> <img src=\"what?.png\" alt=\"what\" /></p>\n";
+    let actual = evaluate_file(&post, outdir.path()).and_then(|post| match post {
+        Output::Set(fields) => fields["content"].eval(),
+        other => Ok(other),
+    });
+    assert_eq!(Ok(Output::String(expected.to_string())), actual);
+    assert!(outdir.path().join("page.html").is_file());
+}
+
+#[test]
+fn markdown_path_should_build_an_html_page() {
+    let tmpdir = TempDir::new("src").unwrap();
+    create_file(tmpdir.path(), "post.md", "+++\ntitle = \"Post\"\n+++\nText\n");
+    let outdir = TempDir::new("out").unwrap();
+    let path_expr = Ast::Val(Val::Path("post.md".to_string()));
+
+    let actual = Evaluator::new(&path_expr, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
+    assert_eq!(Ok(Output::String("post.html".to_string())), actual);
+
+    let mut page = String::new();
+    File::open(outdir.path().join("post.html")).unwrap().read_to_string(&mut page).unwrap();
+    assert_eq!("<p>Text</p>\n", page);
+}
+
+#[test]
+fn collection_should_build_the_files_and_sort_them_by_date() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let posts = tmpdir.path().join("posts");
+    create_dir_all(&posts).unwrap();
+    create_file(&posts, "a.md", "+++\ntitle = \"A\"\ndate = 2018-02-01\n+++\nA\n");
+    create_file(&posts, "b.foil", "+++\ntitle = \"B\"\ndate = 2018-01-01\nslug = \"bee\"\n+++\n\"B\"");
+    create_file(&posts, "c.md", "C\n");
+    create_file(&posts, "notes.txt", "not content");
+    let outdir = TempDir::new("out").unwrap();
+    let expr = Ast::Collection(0, "posts".to_string());
+
+    let item = |fields: Vec<(&str, &str)>| {
+        let fields = fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), Thunk::evaluated(Output::String(value.to_string()))))
+            .collect();
+        Output::Set(fields)
+    };
+    let expected = Ok(Output::List(vec![
+        item(vec![("slug", "c"), ("url", "posts/c.html")]),
+        item(vec![("title", "B"), ("date", "2018-01-01"), ("slug", "bee"), ("url", "posts/b.html")]),
+        item(vec![("title", "A"), ("date", "2018-02-01"), ("slug", "a"), ("url", "posts/a.html")]),
+    ]));
+    let actual = Evaluator::new(&expr, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
+    assert_eq!(expected, actual);
+
+    let mut page = String::new();
+    File::open(outdir.path().join("posts/b.html")).unwrap().read_to_string(&mut page).unwrap();
+    assert_eq!("B", page);
+    assert!(outdir.path().join("posts/a.html").is_file());
+    assert!(!outdir.path().join("posts/notes.txt").exists());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn collection_should_return_NotFile_for_non_existing_directories() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let outdir = TempDir::new("out").unwrap();
+    let expr = Ast::Collection(0, "posts".to_string());
+
+    let expected = Err(EvalError::NotFile(vec![tmpdir.path().join("posts").to_str().unwrap().to_string()]));
+    let actual = Evaluator::new(&expr, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn glob_path_should_copy_every_match_and_return_a_sorted_list() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let gallery = tmpdir.path().join("gallery");
+    create_dir_all(&gallery).unwrap();
+    create_file(&gallery, "b.jpg", "b");
+    create_file(&gallery, "a.jpg", "a");
+    create_file(&gallery, "c.png", "c");
+    let outdir = TempDir::new("out").unwrap();
+    let path_expr = Ast::Val(Val::Path("gallery/*.jpg".to_string()));
+
+    let expected = Ok(Output::List(vec![
+        Output::String("gallery/a.jpg".to_string()),
+        Output::String("gallery/b.jpg".to_string()),
+    ]));
+    let actual = Evaluator::new(&path_expr, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
+    assert_eq!(expected, actual);
+    assert!(outdir.path().join("gallery/a.jpg").is_file());
+    assert!(outdir.path().join("gallery/b.jpg").is_file());
+    assert!(!outdir.path().join("gallery/c.png").exists());
+}
+
+#[test]
+fn recursive_glob_path_should_build_pages() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let nested = tmpdir.path().join("docs/guide");
+    create_dir_all(&nested).unwrap();
+    create_file(&tmpdir.path().join("docs"), "index.foil", "\"index\"");
+    create_file(&nested, "intro.md", "Intro\n");
+    let outdir = TempDir::new("out").unwrap();
+    let path_expr = Ast::Val(Val::Path("docs/**/*.*".to_string()));
+
+    let expected = Ok(Output::List(vec![
+        Output::String("docs/guide/intro.html".to_string()),
+        Output::String("docs/index.html".to_string()),
+    ]));
+    let actual = Evaluator::new(&path_expr, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
+    assert_eq!(expected, actual);
+    assert!(outdir.path().join("docs/guide/intro.html").is_file());
+    assert!(outdir.path().join("docs/index.html").is_file());
+}
+
+#[test]
+fn glob_path_should_return_output_paths_of_pages_and_copied_files() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let docs = tmpdir.path().join("docs");
+    create_dir_all(&docs).unwrap();
+    create_file(&docs, "a.foil", "\"a\"");
+    create_file(&docs, "b.png", "png");
+    create_file(&docs, "c.md", "C\n");
+    let outdir = TempDir::new("out").unwrap();
+    let path_expr = Ast::Val(Val::Path("docs/*".to_string()));
+
+    let expected = Ok(Output::List(vec![
+        Output::String("docs/a.html".to_string()),
+        Output::String("docs/b.png".to_string()),
+        Output::String("docs/c.html".to_string()),
+    ]));
+    let actual = Evaluator::new(&path_expr, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
     assert_eq!(expected, actual);
+    assert!(outdir.path().join("docs/b.png").is_file());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn glob_path_matching_files_outside_of_the_source_directory_should_return_InvalidGlob() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let src = tmpdir.path().join("src");
+    create_dir_all(&src).unwrap();
+    create_file(tmpdir.path(), "secret.png", "png");
+    let outdir = TempDir::new("out").unwrap();
+    let path_expr = Ast::Val(Val::Path("../*.png".to_string()));
+
+    let actual = Evaluator::new(&path_expr, Env::new(), src.join("index.foil"), outdir.path().to_path_buf()).eval();
+    match actual {
+        Err(EvalError::InvalidGlob(ref pattern, _)) => assert_eq!("../*.png", pattern),
+        other => panic!("Expected InvalidGlob, got {:?}", other),
+    }
+}
+
+#[test]
+fn paths_without_a_star_should_not_be_globs() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let img = tmpdir.path().join("img");
+    create_dir_all(&img).unwrap();
+    create_file(&img, "[old].png", "png");
+    create_file(&img, "o.png", "png");
+    let outdir = TempDir::new("out").unwrap();
+    create_dir_all(outdir.path().join("img")).unwrap();
+    let path_expr = Ast::Val(Val::Path("img/[old].png".to_string()));
+
+    let actual = Evaluator::new(&path_expr, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
+    assert_eq!(Ok(Output::String("img/[old].png".to_string())), actual);
+    assert!(outdir.path().join("img/[old].png").is_file());
+    assert!(!outdir.path().join("img/o.png").exists());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn invalid_glob_path_should_return_InvalidGlob() {
+    let tmpdir = TempDir::new("src").unwrap();
+    let outdir = TempDir::new("out").unwrap();
+    let path_expr = Ast::Val(Val::Path("gallery/[*.jpg".to_string()));
+
+    let actual = Evaluator::new(&path_expr, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
+    match actual {
+        Err(EvalError::InvalidGlob(ref pattern, _)) => assert_eq!("gallery/[*.jpg", pattern),
+        other => panic!("Expected InvalidGlob, got {:?}", other),
+    }
 }
 
 #[test]
@@ -276,11 +1077,10 @@ fn should_return_NotFile_error_for_non_existing_Path_expression() {
     
     // Prepare expected output
     let full_path = tmp_working_dir.join(file_name);
-    let expected = Err(EvalError::NotFile(full_path.to_str().unwrap().to_string()));
+    let expected = Err(EvalError::NotFile(vec![full_path.to_str().unwrap().to_string()]));
     
     // Prepare actual output
-    let scope = OpenScope::new();
-    let actual = Evaluator::new(&import_expr, Scope::Open(&scope), tmp_working_dir.join("file.foil"), tmp_out_dir).eval();
+    let actual = Evaluator::new(&import_expr, Env::new(), tmp_working_dir.join("file.foil"), tmp_out_dir).eval();
 
     assert_eq!(expected, actual);
 }
@@ -295,8 +1095,7 @@ fn should_evaluate_html_tag_correctly() {
 
     let expected = Ok(Output::String("<html></html>".to_string()));
 
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&input,  Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
 
     assert_eq!(expected, actual);
 }
@@ -312,11 +1111,11 @@ fn should_evaluate_html_attributes_correctly() {
         attributes: vec![
             SetField{
                 name: "class".to_string(),
-                value: Ast::Val(Val::String("test".to_string()))
+                value: Rc::new(Ast::Val(Val::String("test".to_string())))
             },
             SetField{
                 name: "id".to_string(),
-                value: Ast::Val(Val::Int(1)),
+                value: Rc::new(Ast::Val(Val::Int(1))),
             },
         ],
         children: vec![],
@@ -324,8 +1123,7 @@ fn should_evaluate_html_attributes_correctly() {
 
     let expected = Ok(Output::String("<div class=\"test\" id=\"1\"></div>".to_string()));
 
-    let scope =  OpenScope::new();
-    let actual = Evaluator::without_files(&input, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
 
     assert_eq!(expected, actual);
 }
@@ -351,8 +1149,7 @@ fn should_evaluate_html_children_correctly() {
 
     let expected = Ok(Output::String("<div><p></p>test</div>".to_string()));
 
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&input, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
 
     assert_eq!(expected, actual);
 }
@@ -369,8 +1166,7 @@ fn should_evaluate_selfclosing_tag_correctly() {
 
     let expected = Ok(Output::String("<br/>".to_string()));
 
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&input, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
 
     assert_eq!(expected, actual);
 }
@@ -385,20 +1181,225 @@ fn should_evaluate_attributes_for_selfclosing_tags_correctly() {
         attributes: vec![
             SetField{
                 name: "rel".to_string(),
-                value: Ast::Val(Val::String("stylesheet".to_string())),
+                value: Rc::new(Ast::Val(Val::String("stylesheet".to_string()))),
             },
             SetField{
                 name: "type".to_string(),
-                value: Ast::Val(Val::String("text/css".to_string())),
+                value: Rc::new(Ast::Val(Val::String("text/css".to_string()))),
             },
         ],
     };
 
     let expected = Ok(Output::String("<link rel=\"stylesheet\" type=\"text/css\"/>".to_string()));
 
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&input, Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
     
     assert_eq!(expected, actual);
 }
 
+
+#[test]
+fn should_evaluate_set_fields_lazily() {
+    // let x = 2 in set { a = + x 1, b = "test" }
+    let set = Ast::Set(vec![
+        SetField{
+            name: "a".to_string(),
+            value: Rc::new(Ast::BinOp(
+                BinOp::Add,
+                Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                Rc::new(Ast::Val(Val::Int(1))))),
+        },
+        SetField{
+            name: "b".to_string(),
+            value: Rc::new(Ast::Val(Val::String("test".to_string()))),
+        },
+    ]);
+    let input = Ast::Let(
+        vec![SetField{name: "x".to_string(), value: Rc::new(Ast::Val(Val::Int(2)))}],
+        Rc::new(set));
+
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
+
+    let fields = match actual {
+        Ok(Output::Set(fields)) => fields,
+        other => panic!("Expected a set but got {:?}", other),
+    };
+    assert_eq!(Ok(Output::Int(3)), fields["a"].eval());
+    assert_eq!(Ok(Output::String("test".to_string())), fields["b"].eval());
+    assert_eq!("set { a = 3, b = \"test\" }", format!("{}", Output::Set(fields)));
+}
+
+#[test]
+fn printing_a_set_should_not_evaluate_its_fields() {
+    // set { a = + 1 2, b = x }
+    let input = Ast::Set(vec![
+        SetField{
+            name: "a".to_string(),
+            value: Rc::new(Ast::BinOp(
+                BinOp::Add,
+                Rc::new(Ast::Val(Val::Int(1))),
+                Rc::new(Ast::Val(Val::Int(2))))),
+        },
+        SetField{
+            name: "b".to_string(),
+            value: Rc::new(Ast::Id(Id(0, "x".to_string()))),
+        },
+    ]);
+
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
+
+    let fields = match actual {
+        Ok(Output::Set(fields)) => fields,
+        other => panic!("Expected a set but got {:?}", other),
+    };
+    assert_eq!("set { a = <lazy>, b = <lazy> }", format!("{}", Output::Set(fields.clone())));
+    assert_eq!(Ok(Output::Int(3)), fields["a"].eval());
+    assert_eq!("set { a = 3, b = <lazy> }", format!("{}", Output::Set(fields.clone())));
+    assert!(!fields["b"].is_evaluated());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn set_in_html_should_return_NotStringable_error() {
+    // div { set { a = 1 } }
+    let set = Ast::Set(vec![
+        SetField{ name: "a".to_string(), value: Rc::new(Ast::Val(Val::Int(1))) },
+    ]);
+    let input = Ast::Html{
+        tag_name: "div".to_string(),
+        attributes: vec![],
+        children: vec![set.clone()],
+    };
+
+    let expected_set = Evaluator::without_files(&set, Env::new()).eval().unwrap();
+    let expected = Err(EvalError::NotStringable(expected_set));
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn field_access_should_work_with_nested_sets() {
+    // set { nav = set { title = "Home" } }.nav.title
+    let inner = Ast::Set(vec![
+        SetField{ name: "title".to_string(), value: Rc::new(Ast::Val(Val::String("Home".to_string()))) },
+    ]);
+    let outer = Ast::Set(vec![
+        SetField{ name: "nav".to_string(), value: Rc::new(inner) },
+    ]);
+    let input = Ast::Field(
+        Rc::new(Ast::Field(Rc::new(outer), Id(0, "nav".to_string()))),
+        Id(0, "title".to_string()));
+
+    let expected = Ok(Output::String("Home".to_string()));
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn field_access_should_return_FieldNotFound_for_missing_fields() {
+    // set { a = 1 }.b
+    let set = Ast::Set(vec![
+        SetField{ name: "a".to_string(), value: Rc::new(Ast::Val(Val::Int(1))) },
+    ]);
+    let input = Ast::Field(Rc::new(set), Id(14, "b".to_string()));
+
+    let expected = Err(EvalError::FieldNotFound(Id(14, "b".to_string())));
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn field_access_should_work_on_imported_files() {
+    let tmpdir = TempDir::new("test").unwrap();
+    create_dir_all(tmpdir.path()).unwrap();
+
+    let contents = "set { site = set { title = \"My site\" } }".as_bytes();
+    let import_file = tmpdir.path().join("config.foil");
+    {
+        let mut f = File::create(&import_file).unwrap();
+        f.write_all(contents).unwrap();
+        f.sync_all().unwrap();
+    }
+
+    let outdir = TempDir::new("out").unwrap();
+    create_dir_all(outdir.path()).unwrap();
+
+    // (import "config.foil").site.title
+    let import = Ast::Import(0, "config.foil".to_string());
+    let input = Ast::Field(
+        Rc::new(Ast::Field(Rc::new(import), Id(0, "site".to_string()))),
+        Id(0, "title".to_string()));
+
+    let expected = Ok(Output::String("My site".to_string()));
+    let actual = Evaluator::new(&input, Env::new(), tmpdir.path().join("index.foil"), outdir.path().to_path_buf()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_evaluate_list_elements_in_order() {
+    // [1 + 1 1 "a"]
+    let input = Ast::List(vec![
+        Ast::Val(Val::Int(1)),
+        Ast::BinOp(BinOp::Add, Rc::new(Ast::Val(Val::Int(1))), Rc::new(Ast::Val(Val::Int(1)))),
+        Ast::Val(Val::String("a".to_string())),
+    ]);
+
+    let expected = Ok(Output::List(vec![
+        Output::Int(1),
+        Output::Int(2),
+        Output::String("a".to_string()),
+    ]));
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_stringify_lists_in_html_children() {
+    // ul { [ li "a" li "b" ] }
+    let item = |text: &str| Ast::Html{
+        tag_name: "li".to_string(),
+        attributes: vec![],
+        children: vec![Ast::Val(Val::String(text.to_string()))],
+    };
+    let input = Ast::Html{
+        tag_name: "ul".to_string(),
+        attributes: vec![],
+        children: vec![Ast::List(vec![item("a"), item("b")])],
+    };
+
+    let expected = Ok(Output::String("<ul><li>a</li><li>b</li></ul>".to_string()));
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn if_should_only_evaluate_the_taken_branch() {
+    // if == 1 1 then "yes" else undefined
+    let input = Ast::If(
+        Rc::new(Ast::BinOp(
+                BinOp::Equals,
+                Rc::new(Ast::Val(Val::Int(1))),
+                Rc::new(Ast::Val(Val::Int(1))))),
+        Rc::new(Ast::Val(Val::String("yes".to_string()))),
+        Rc::new(Ast::Id(Id(0, "undefined".to_string()))));
+
+    let expected = Ok(Output::String("yes".to_string()));
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn if_should_return_NotABool_for_non_bool_conditions() {
+    // if 1 then 2 else 3
+    let input = Ast::If(
+        Rc::new(Ast::Val(Val::Int(1))),
+        Rc::new(Ast::Val(Val::Int(2))),
+        Rc::new(Ast::Val(Val::Int(3))));
+
+    let expected = Err(EvalError::NotABool(Output::Int(1)));
+    let actual = Evaluator::without_files(&input, Env::new()).eval();
+    assert_eq!(expected, actual);
+}
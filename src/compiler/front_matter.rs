@@ -0,0 +1,63 @@
+//! Front matter of content files, i.e. metadata written in TOML between two `+++` lines at the
+//! start of a `.foil` or `.md` file.
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use compiler::errors::EvalError;
+use compiler::models::{Output, Thunk};
+use compiler::data::evaluate_toml;
+
+/// The line that starts and ends the front matter.
+const FRONT_MATTER_DELIMITER: &'static str = "+++";
+
+/// Splits the text into the front matter, if there is any, and the rest of the text.
+pub fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+    let mut lines = text.split('\n');
+    let first_line = match lines.next() {
+        Some(line) if line.trim_end() == FRONT_MATTER_DELIMITER => line,
+        _ => { return (None, text); },
+    };
+
+    let start = (first_line.len() + 1).min(text.len());
+    let mut end = start;
+    for line in lines {
+        if line.trim_end() == FRONT_MATTER_DELIMITER {
+            let body_start = (end + line.len() + 1).min(text.len());
+            return (Some(&text[start..end]), &text[body_start..]);
+        }
+        end += line.len() + 1;
+    }
+    (None, text)
+}
+
+/// Parses the front matter into the fields of a set. Files without front matter have no fields.
+///
+/// # Errors
+/// `EvalError::InvalidData` if the front matter is not a valid TOML document or not a table.
+pub fn parse_front_matter(front_matter: Option<&str>, file_path: &Path) -> Result<BTreeMap<String, Thunk>, EvalError> {
+    match front_matter.map(|front_matter| evaluate_toml(front_matter, file_path)) {
+        None => Ok(BTreeMap::new()),
+        Some(Ok(Output::Set(fields))) => Ok(fields),
+        Some(Ok(output)) => {
+            let message = format!("the front matter is not a table: {}", output);
+            Err(EvalError::InvalidData(file_path.to_path_buf(), None, message))
+        },
+        // The front matter starts on the second line of the file.
+        Some(Err(EvalError::InvalidData(path, line, message))) => {
+            Err(EvalError::InvalidData(path, line.map(|line| line + 1), message))
+        },
+        Some(Err(err)) => Err(err),
+    }
+}
+
+/// Replaces the front matter of foil code with whitespace, so that the code can be evaluated
+/// and positions in the code stay the same.
+pub fn hide_front_matter(code: &str) -> String {
+    let (_, body) = split_front_matter(code);
+    let front_matter_len = code.len() - body.len();
+    let hidden: String = code[..front_matter_len]
+        .chars()
+        .map(|c| if c == '\n' { '\n' } else { ' ' })
+        .collect();
+    hidden + body
+}
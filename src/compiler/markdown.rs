@@ -0,0 +1,104 @@
+//! Rendering of markdown content files.
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::rc::Rc;
+
+use pulldown_cmark::{Parser, Event, Tag, html};
+
+use compiler::evaluator::{EvalResult, Context, build_path, is_page};
+use compiler::errors::EvalError;
+use compiler::models::{Output, Thunk};
+use compiler::front_matter::{split_front_matter, parse_front_matter};
+
+/// The field of a rendered markdown file that holds the html.
+pub const CONTENT_FIELD: &'static str = "content";
+
+/// Returns `true` if the file is a markdown file.
+pub fn is_markdown_file(file_path: &Path) -> bool {
+    file_path.extension() == Some(OsStr::new("md"))
+}
+
+/// Renders a markdown file into a set of the fields of its front matter and the field `content`
+/// which holds the rendered html.
+///
+/// The front matter is optional and is written in TOML between two `+++` lines at the start of
+/// the file. Relative links and images are built or copied into `out_dir` like path literals,
+/// links to `.foil` and `.md` files are changed to point to the built html files.
+///
+/// # Arguments
+/// `text` - the contents of the file.
+/// `file_path` - the path to the file.
+/// `out_dir` - the output directory.
+/// `context` - the state of the build.
+///
+/// # Errors
+/// `EvalError::InvalidData` if the front matter could not be parsed.
+/// Any error that occurs while building the linked files.
+pub fn evaluate_markdown(text: &str, file_path: &Path, out_dir: &Path, context: &Rc<Context>) -> EvalResult {
+    let (front_matter, body) = split_front_matter(text);
+
+    let mut fields = match parse_front_matter(front_matter, file_path) {
+        Ok(fields) => fields,
+        Err(err) => { return Err(err); },
+    };
+
+    let src_dir = file_path.parent().unwrap_or(Path::new("./"));
+    let mut events = Vec::new();
+    for event in Parser::new(body) {
+        let event = match event {
+            Event::Start(Tag::Link(url, title)) => {
+                resolve_link(url, src_dir, out_dir, context).map(|url| Event::Start(Tag::Link(url, title)))
+            },
+            Event::Start(Tag::Image(url, title)) => {
+                resolve_link(url, src_dir, out_dir, context).map(|url| Event::Start(Tag::Image(url, title)))
+            },
+            event => Ok(event),
+        };
+        match event {
+            Ok(event) => events.push(event),
+            Err(err) => { return Err(err); },
+        }
+    }
+
+    let mut content = String::new();
+    html::push_html(&mut content, events.into_iter());
+    fields.insert(CONTENT_FIELD.to_string(), Thunk::evaluated(Output::String(content)));
+    Ok(Output::Set(fields))
+}
+
+/// Builds or copies the target of a relative link and returns the url of the output.
+/// Links to `.foil` and `.md` files are changed to point to the built html files. Pages that are
+/// already being evaluated, e.g. posts that link to each other, are left to that evaluation.
+/// Absolute urls, urls with a scheme, links within the page and links that are not to a file in
+/// the source directory are returned unchanged.
+fn resolve_link<'a>(url: Cow<'a, str>, src_dir: &Path, out_dir: &Path, context: &Rc<Context>) -> Result<Cow<'a, str>, EvalError> {
+    if url.is_empty() || url.starts_with('/') || url.starts_with('#') || url.contains(':') {
+        return Ok(url);
+    }
+
+    let (file, suffix) = match url.find(|c| c == '?' || c == '#') {
+        Some(i) => (&url[..i], &url[i..]),
+        None => (&url[..], ""),
+    };
+    let file_path = src_dir.join(file);
+    if !file_path.is_file() {
+        return Ok(url.clone());
+    }
+
+    if !is_page(&file_path) {
+        return build_path(file, src_dir, out_dir, context).map(|_| url.clone());
+    }
+
+    let in_progress = match file_path.canonicalize() {
+        Ok(canonical) => context.is_evaluating(&canonical),
+        Err(err) => { return Err(EvalError::IO(err)); },
+    };
+    if !in_progress {
+        if let Err(err) = build_path(file, src_dir, out_dir, context) {
+            return Err(err);
+        }
+    }
+    let page = Path::new(file).with_extension("html");
+    Ok(Cow::Owned(format!("{}{}", page.to_str().unwrap_or("None"), suffix)))
+}
@@ -5,6 +5,17 @@ pub mod models;
 pub mod errors;
 
 mod compiler;
-pub use self::compiler::{build_file, evaluate_file, copy_file, write_to_file};
+mod data;
+mod markdown;
+mod front_matter;
+pub use self::compiler::{
+    build_file, 
+    build_file_with_context, 
+    evaluate_file, 
+    evaluate_file_with_context, 
+    evaluate_contents_with_context, 
+    copy_file, 
+    write_to_file
+};
 
 #[cfg(test)] mod tests;
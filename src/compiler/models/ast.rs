@@ -1,20 +1,44 @@
-use compiler::models::{BinOp, Val};
+use compiler::models::{BinOp, UnaryOp, Val};
 use std::fmt::{Display, Formatter, self};
+use std::collections::HashSet;
+use std::rc::Rc;
 
 /// AST - Abstract Syntax Tree
 #[derive(PartialEq)]
 #[derive(Debug)]
 #[derive(Clone)]
 pub enum Ast {
-    BinOp(BinOp, Box<Ast>, Box<Ast>),
+    BinOp(BinOp, Rc<Ast>, Rc<Ast>),
+    UnaryOp(UnaryOp, Rc<Ast>),
     Val(Val),
     Set(Set),
-    Let(Box<SetField>, Box<Ast>),
-    Fn(String, Box<Ast>),
-    Call(Box<Ast>, Box<Ast>),
+
+    /// Represents `let a = .., b = .. in expr`. The bindings can refer to themselves and to each
+    /// other.
+    Let(Vec<SetField>, Rc<Ast>),
+
+    Fn(String, Rc<Ast>),
+
+    /// Represents a call of a function with one or more arguments, e.g. `(f 1 2)`. The function
+    /// is applied to one argument after the other.
+    Call(Rc<Ast>, Vec<Arg>),
     Id(Id),
     Import(usize, String),
 
+    /// Represents `collection <dir>`, the content files of a directory. Holds the position and
+    /// the path of the directory. `collection` is only a keyword in front of a path, otherwise
+    /// it is a name like any other.
+    Collection(usize, String),
+
+    /// Represents access of a field in a set, e.g. `page.title`
+    Field(Rc<Ast>, Id),
+
+    /// Represents a list literal, e.g. `[1 2 3]`
+    List(Vec<Ast>),
+
+    /// Represents a conditional expression `if cond then a else b`
+    If(Rc<Ast>, Rc<Ast>, Rc<Ast>),
+
     /// Represents an HTML element
     Html{
         tag_name: String, 
@@ -29,17 +53,107 @@ pub enum Ast {
     },
 }
 
+impl Ast {
+    /// Returns the names of all variables that are used in the expression but not defined in it.
+    pub fn free_variables(&self) -> HashSet<String> {
+        let mut free = HashSet::new();
+        self.collect_free_variables(&mut Vec::new(), &mut free);
+        free
+    }
+
+    fn collect_free_variables<'a>(&'a self, bound: &mut Vec<&'a str>, free: &mut HashSet<String>) {
+        match self {
+            &Ast::BinOp(_, ref l, ref r) => {
+                l.collect_free_variables(bound, free);
+                r.collect_free_variables(bound, free);
+            },
+            &Ast::Call(ref func, ref args) => {
+                func.collect_free_variables(bound, free);
+                for arg in args.iter() {
+                    arg.1.collect_free_variables(bound, free);
+                }
+            },
+            &Ast::UnaryOp(_, ref expr) | &Ast::Field(ref expr, _) => {
+                expr.collect_free_variables(bound, free);
+            },
+            &Ast::Val(_) | &Ast::Import(..) | &Ast::Collection(..) => {},
+            &Ast::Set(ref fields) | &Ast::HtmlClosed{attributes: ref fields, ..} => {
+                for field in fields.iter() {
+                    field.value.collect_free_variables(bound, free);
+                }
+            },
+            &Ast::Let(ref bindings, ref expr) => {
+                let len = bound.len();
+                bound.extend(bindings.iter().map(|binding| binding.name.as_str()));
+                for binding in bindings.iter() {
+                    binding.value.collect_free_variables(bound, free);
+                }
+                expr.collect_free_variables(bound, free);
+                bound.truncate(len);
+            },
+            &Ast::Fn(ref param, ref expr) => {
+                bound.push(param);
+                expr.collect_free_variables(bound, free);
+                bound.pop();
+            },
+            &Ast::Id(ref id) => {
+                if !bound.contains(&id.1.as_str()) {
+                    free.insert(id.1.clone());
+                }
+            },
+            &Ast::List(ref items) => {
+                for item in items.iter() {
+                    item.collect_free_variables(bound, free);
+                }
+            },
+            &Ast::If(ref cond, ref then, ref otherwise) => {
+                cond.collect_free_variables(bound, free);
+                then.collect_free_variables(bound, free);
+                otherwise.collect_free_variables(bound, free);
+            },
+            &Ast::Html{ref attributes, ref children, ..} => {
+                for attribute in attributes.iter() {
+                    attribute.value.collect_free_variables(bound, free);
+                }
+                for child in children.iter() {
+                    child.collect_free_variables(bound, free);
+                }
+            },
+        }
+    }
+}
+
 impl Display for Ast {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
             &Ast::BinOp(ref op, ref l, ref r) => write!(f, "{} {} {}", op, l, r),
+            &Ast::UnaryOp(ref op, ref expr) => write!(f, "{}{}", op, expr),
             &Ast::Val(ref val) => write!(f, "{}", val),
             &Ast::Set(_) => write!(f, "set.."),
-            &Ast::Let(ref field, ref expr) => write!(f, "let {}={} in {}", field.name, field.value, expr),
+            &Ast::Let(ref bindings, ref expr) => {
+                write!(f, "let ")?;
+                for (i, binding) in bindings.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}={}", binding.name, binding.value)?;
+                }
+                write!(f, " in {}", expr)
+            },
             &Ast::Fn(ref param, ref expr) => write!(f, "fn {}: {}", param, expr),
-            &Ast::Call(ref param, ref expr) => write!(f, "({} {})", param, expr),
+            &Ast::Call(ref func, ref args) => {
+                write!(f, "({}", func)?;
+                for arg in args.iter() {
+                    write!(f, " {}", arg.1)?;
+                }
+                write!(f, ")")
+            },
             &Ast::Id(ref id) => write!(f, "{}", id.1),
             &Ast::Import(_, ref file) => write!(f, "import {}", file),
+            &Ast::Collection(_, ref dir) => write!(f, "collection <{}>", dir),
+            &Ast::Field(ref expr, ref field) => write!(f, "{}.{}", expr, field.1),
+            &Ast::List(_) => write!(f, "[..]"),
+            &Ast::If(ref cond, ref then, ref otherwise) => write!(f, "if {} then {} else {}", cond, then, otherwise),
             &Ast::Html{..} | &Ast::HtmlClosed{..} => write!(f, "html!.."),
         }
     }
@@ -50,6 +164,12 @@ impl Display for Ast {
 #[derive(Clone)]
 pub struct Id(pub usize, pub String);
 
+/// An argument of a call together with its position.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Arg(pub usize, pub Rc<Ast>);
+
 pub type Set = Vec<SetField>;
 
 #[derive(PartialEq)]
@@ -57,5 +177,5 @@ pub type Set = Vec<SetField>;
 #[derive(Clone)]
 pub struct SetField {
     pub name: String,
-    pub value: Ast,
+    pub value: Rc<Ast>,
 }
@@ -0,0 +1,61 @@
+use compiler::models::Output;
+use compiler::evaluator::EvalResult;
+use std::fmt::{Debug, Formatter, self};
+
+/// The signature of the native implementation of a builtin function.
+/// The function is called with exactly `arity` arguments.
+pub type BuiltinFn = fn(Vec<Output>) -> EvalResult;
+
+/// A function that is implemented in Rust.
+///
+/// Builtin functions are curried just like foil functions. Every call adds an argument and when
+/// `arity` arguments have been collected the native function is called.
+#[derive(Clone)]
+pub struct Builtin {
+    pub name: &'static str,
+    arity: usize,
+    args: Vec<Output>,
+    func: BuiltinFn,
+}
+
+impl Builtin {
+    pub fn new(name: &'static str, arity: usize, func: BuiltinFn) -> Self {
+        Builtin{
+            name: name,
+            arity: arity,
+            args: vec![],
+            func: func,
+        }
+    }
+
+    /// Applies the function to the argument `arg`.
+    /// Returns a new partially applied builtin if more arguments are needed, otherwise the result
+    /// of the native function.
+    pub fn apply(&self, arg: Output) -> EvalResult {
+        let mut args = self.args.clone();
+        args.push(arg);
+
+        if args.len() < self.arity {
+            Ok(Output::Builtin(Builtin{
+                name: self.name,
+                arity: self.arity,
+                args: args,
+                func: self.func,
+            }))
+        } else {
+            (self.func)(args)
+        }
+    }
+}
+
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Builtin) -> bool {
+        self.name == other.name && self.args == other.args
+    }
+}
+
+impl Debug for Builtin {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Builtin({}, {:?})", self.name, self.args)
+    }
+}
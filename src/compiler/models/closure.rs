@@ -1,35 +1,72 @@
 use std::collections::HashMap;
-use compiler::models::Ast;
-use compiler::evaluator::{OpenScope, ClosedScope, Scope, Evaluator, EvalResult};
+use std::path::PathBuf;
+use std::rc::Rc;
+use compiler::models::{Ast, Output, Thunk};
+use compiler::evaluator::{Env, Context, Evaluator, EvalResult, Step};
 
 #[derive(PartialEq)]
 #[derive(Debug)]
 #[derive(Clone)]
 pub struct Closure {
     pub param_name: String,
-    pub expr: Ast,
-    scope: ClosedScope,
+    pub expr: Rc<Ast>,
+    env: Env,
+
+    /// The file in which the closure was defined.
+    file_path: Option<PathBuf>,
+
+    /// The output directory of the file in which the closure was defined.
+    out_path: Option<PathBuf>,
+
+    /// The state of the build in which the closure was defined.
+    context: Rc<Context>,
 }
 
 impl Closure {
-    pub fn new(param_name: String, expr: Ast, scope: ClosedScope) -> Self {
+    pub fn new(param_name: String, 
+               expr: Rc<Ast>, 
+               env: Env, 
+               file_path: Option<PathBuf>, 
+               out_path: Option<PathBuf>, 
+               context: Rc<Context>) -> Self {
         Closure{
             param_name: param_name,
-            scope: scope, 
-            expr: expr
+            env: env, 
+            expr: expr,
+            file_path: file_path,
+            out_path: out_path,
+            context: context,
         }
     }
 
-    pub fn eval(&self, param_value: &Ast) -> EvalResult {
-        let mut map: HashMap<&str, _> = HashMap::new();
-        let param_eval = Evaluator::without_files(param_value, Scope::Closed(&self.scope));
-        map.insert(&self.param_name, param_eval);
-
-        let child_scope = OpenScope{
-                map: map, 
-                parent: Some(Scope::Closed(&self.scope))
-            };
-        let eval = Evaluator::without_files(&self.expr, Scope::Open(&child_scope));
-        eval.eval()
+    /// Calls the closure with an unevaluated parameter.
+    ///
+    /// # Arguments
+    /// `param_value` - the argument, in the environment of the caller. It is only evaluated if
+    /// the parameter is used.
+    pub fn eval(&self, param_value: Thunk) -> EvalResult {
+        self.evaluator(param_value).eval()
+    }
+
+    /// Calls the closure with an unevaluated parameter, up to the first call in tail position of
+    /// the closure.
+    pub fn eval_step(&self, param_value: Thunk) -> Step {
+        self.evaluator(param_value).eval_step()
+    }
+
+    fn evaluator<'a>(&'a self, param_value: Thunk) -> Evaluator<'a> {
+        let mut vars = HashMap::new();
+        vars.insert(self.param_name.clone(), param_value);
+
+        Evaluator::with_context(&self.expr, 
+                                self.env.with_vars(vars), 
+                                self.file_path.clone(), 
+                                self.out_path.clone(),
+                                self.context.clone())
+    }
+
+    /// Calls the closure with an already evaluated value as the parameter.
+    pub fn apply(&self, param_value: Output) -> EvalResult {
+        self.eval(Thunk::evaluated(param_value))
     }
 }
@@ -1,9 +1,13 @@
 pub mod tokens;
 pub mod ast;
 mod closure;
+mod thunk;
+mod builtin;
 mod output;
 
 pub use self::ast::*;
 pub use self::tokens::*;
 pub use self::closure::*;
+pub use self::thunk::*;
+pub use self::builtin::*;
 pub use self::output::*;
@@ -1,5 +1,9 @@
 use compiler::errors::EvalError;
 use super::closure::Closure;
+use super::thunk::Thunk;
+use super::builtin::Builtin;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter, self};
 
 /// Represents the output of an evaluated expression tree.
@@ -12,6 +16,14 @@ pub enum Output {
     Bool(bool),
     String(String),
     Fn(Closure),
+    Builtin(Builtin),
+
+    /// A set of named fields. The fields are evaluated lazily, i.e. only when they are accessed.
+    Set(BTreeMap<String, Thunk>),
+
+    /// A list of values. When converted to a string the elements are converted to strings and
+    /// concatenated in order.
+    List(Vec<Output>),
 }
 
 impl Output {
@@ -22,7 +34,8 @@ impl Output {
         match self {
             &Output::Int(_) | &Output::Double(_) | 
             &Output::Bool(_) | &Output::String(_) => true,
-            &Output::Fn(_) => false,
+            &Output::Fn(_) | &Output::Builtin(_) | &Output::Set(_) => false,
+            &Output::List(ref items) => items.iter().all(Output::is_stringable),
         }
     }
 
@@ -32,9 +45,95 @@ impl Output {
             Output::Double(x) => Ok(format!("{}", x)),
             Output::Bool(x) => Ok(format!("{}", x)),
             Output::String(x) => Ok(x),
+            Output::List(items) => {
+                let mut out = String::new();
+                for item in items.into_iter() {
+                    match item.to_string() {
+                        Ok(item) => out.push_str(&item),
+                        Err(err) => { return Err(err); },
+                    }
+                }
+                Ok(out)
+            },
             non_content => Err(EvalError::NotStringable(non_content)),
         }
     }
+
+    /// Structural equality of two values.
+    ///
+    /// Integers and doubles are compared by converting the integer to a double, so `== 1 1.0` is
+    /// `true`. Lists are equal if their elements are equal and sets are equal if they have the
+    /// same field names and the values of the fields are equal. Values of different types are
+    /// never equal.
+    ///
+    /// # Errors
+    /// `EvalError::FnComparison` if a function is compared, since there is no sensible way to
+    /// compare functions.
+    /// Any error that occurs while evaluating the fields of sets.
+    pub fn equals(&self, other: &Output) -> Result<bool, EvalError> {
+        match (self, other) {
+            (&Output::Fn(_), _) | (&Output::Builtin(_), _) |
+            (_, &Output::Fn(_)) | (_, &Output::Builtin(_)) => 
+                Err(EvalError::FnComparison(self.clone(), other.clone())),
+            (&Output::Int(ref l), &Output::Int(ref r)) => Ok(l == r),
+            (&Output::Double(ref l), &Output::Double(ref r)) => Ok(l == r),
+            (&Output::Int(l), &Output::Double(r)) => Ok(l as f64 == r),
+            (&Output::Double(l), &Output::Int(r)) => Ok(l == r as f64),
+            (&Output::String(ref l), &Output::String(ref r)) => Ok(l == r),
+            (&Output::Bool(ref l), &Output::Bool(ref r)) => Ok(l == r),
+            (&Output::List(ref l), &Output::List(ref r)) => {
+                if l.len() != r.len() {
+                    return Ok(false);
+                }
+                for (l, r) in l.iter().zip(r.iter()) {
+                    match l.equals(r) {
+                        Ok(true) => {},
+                        other => { return other; },
+                    }
+                }
+                Ok(true)
+            },
+            (&Output::Set(ref l), &Output::Set(ref r)) => {
+                if l.len() != r.len() || l.keys().zip(r.keys()).any(|(l, r)| l != r) {
+                    return Ok(false);
+                }
+                for (l, r) in l.values().zip(r.values()) {
+                    let equal = match (l.eval(), r.eval()) {
+                        (Ok(l), Ok(r)) => l.equals(&r),
+                        (Err(err), _) | (_, Err(err)) => Err(err),
+                    };
+                    match equal {
+                        Ok(true) => {},
+                        other => { return other; },
+                    }
+                }
+                Ok(true)
+            },
+            (_, _) => Ok(false),
+        }
+    }
+
+    /// Compares two values. Numbers are compared with numbers, strings are compared
+    /// lexicographically and booleans with booleans (`false` < `true`).
+    ///
+    /// # Errors
+    /// `EvalError::NotComparable` if the values are not of comparable types.
+    pub fn compare(&self, other: &Output) -> Result<Ordering, EvalError> {
+        let ordering = match (self, other) {
+            (&Output::Int(ref l), &Output::Int(ref r)) => Some(l.cmp(r)),
+            (&Output::Double(ref l), &Output::Double(ref r)) => l.partial_cmp(r),
+            (&Output::Int(l), &Output::Double(ref r)) => (l as f64).partial_cmp(r),
+            (&Output::Double(ref l), &Output::Int(r)) => l.partial_cmp(&(r as f64)),
+            (&Output::String(ref l), &Output::String(ref r)) => Some(l.cmp(r)),
+            (&Output::Bool(ref l), &Output::Bool(ref r)) => Some(l.cmp(r)),
+            _ => None,
+        };
+
+        match ordering {
+            Some(ordering) => Ok(ordering),
+            None => Err(EvalError::NotComparable(self.clone(), other.clone())),
+        }
+    }
 }
 
 impl Display for Output {
@@ -45,7 +144,33 @@ impl Display for Output {
             &Output::Bool(ref x) => write!(f, "{}", x),
             &Output::String(ref x) => write!(f, "\"{}\"", x),
             &Output::Fn(ref func) => write!(f, "<function {}: {}>", func.param_name, func.expr),
+            &Output::Builtin(ref func) => write!(f, "<builtin {}>", func.name),
+            &Output::Set(ref fields) => {
+                write!(f, "set {{")?;
+                for (i, (name, thunk)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    // Printing never evaluates a field, so fields that were not
+                    // needed yet are printed as `<lazy>` and failed ones as their error.
+                    match thunk.value() {
+                        Some(Ok(value)) => write!(f, " {} = {}", name, value)?,
+                        Some(Err(err)) => write!(f, " {} = <{:?}>", name, err)?,
+                        None => write!(f, " {} = <lazy>", name)?,
+                    }
+                }
+                write!(f, " }}")
+            },
+            &Output::List(ref items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
         }
     }
 }
-
@@ -0,0 +1,200 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use compiler::models::{Ast, BinOp, Output};
+use compiler::evaluator::{Env, Context, Evaluator, EvalResult};
+use compiler::errors::EvalError;
+
+/// An unevaluated expression together with the environment it was defined in.
+/// Used for values that should only be evaluated when they are needed, such as variables,
+/// function arguments and the fields of a set.
+///
+/// The expression is evaluated at most once. The result is shared between all clones of the
+/// thunk.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Thunk {
+    state: Rc<RefCell<ThunkState>>,
+}
+
+#[derive(Debug)]
+enum ThunkState {
+    Unevaluated{
+        expr: Rc<Ast>,
+        env: Env,
+        file_path: Option<PathBuf>,
+        out_path: Option<PathBuf>,
+        context: Rc<Context>,
+    },
+
+    /// The expression is being evaluated. Holds the expression.
+    InProgress(Rc<Ast>),
+    Evaluated(EvalResult),
+}
+
+impl Thunk {
+    pub fn new(expr: Rc<Ast>, 
+               env: Env, 
+               file_path: Option<PathBuf>, 
+               out_path: Option<PathBuf>, 
+               context: Rc<Context>) -> Self {
+        let state = ThunkState::Unevaluated{
+            expr: expr,
+            env: env,
+            file_path: file_path,
+            out_path: out_path,
+            context: context,
+        };
+        Thunk{state: Rc::new(RefCell::new(state))}
+    }
+
+    /// Creates a thunk for an already evaluated value.
+    pub fn evaluated(value: Output) -> Self {
+        Thunk{state: Rc::new(RefCell::new(ThunkState::Evaluated(Ok(value))))}
+    }
+
+    /// Returns `true` if the value of the thunk is already known.
+    pub fn is_evaluated(&self) -> bool {
+        match *self.state.borrow() {
+            ThunkState::Evaluated(_) => true,
+            ThunkState::Unevaluated{..} | ThunkState::InProgress(_) => false,
+        }
+    }
+
+    /// Returns the result of the previous evaluation without evaluating the thunk.
+    pub fn value(&self) -> Option<EvalResult> {
+        match *self.state.borrow() {
+            ThunkState::Evaluated(ref value) => Some(Clone::clone(value)),
+            ThunkState::Unevaluated{..} | ThunkState::InProgress(_) => None,
+        }
+    }
+
+    /// Evaluates the expression in the environment and file it was defined in, or returns the
+    /// result of the previous evaluation.
+    ///
+    /// # Errors
+    /// `EvalError::ValueCycle` if the thunk is already being evaluated, i.e. if its value depends
+    /// on itself.
+    pub fn eval(&self) -> EvalResult {
+        if self.is_evaluated() {
+            return self.eval_expr();
+        }
+        // Arguments are passed unevaluated, so e.g. the accumulator of a tail recursive function
+        // is a long chain of thunks that each need the previous one first. The chain is
+        // evaluated from its end, so that evaluating it doesn't nest. Errors are kept in the
+        // thunks and only returned where they are used.
+        for thunk in self.needed_thunks().iter().rev() {
+            let _ = thunk.eval_expr();
+        }
+        self.eval_expr()
+    }
+
+    /// Evaluates the expression of the thunk, or returns the result of the previous evaluation.
+    fn eval_expr(&self) -> EvalResult {
+        let (expr, env, file_path, out_path, context) = match *self.state.borrow() {
+            ThunkState::Evaluated(ref value) => { return Clone::clone(value); },
+            ThunkState::InProgress(ref expr) => { return Err(EvalError::ValueCycle(expr.to_string())); },
+            ThunkState::Unevaluated{ref expr, ref env, ref file_path, ref out_path, ref context} => 
+                (expr.clone(), env.clone(), file_path.clone(), out_path.clone(), context.clone()),
+        };
+
+        *self.state.borrow_mut() = ThunkState::InProgress(expr.clone());
+        let value = Evaluator::with_context(&expr, env, file_path, out_path, context).eval();
+        // The environment is not needed anymore once the value is known.
+        *self.state.borrow_mut() = ThunkState::Evaluated(Clone::clone(&value));
+        value
+    }
+
+    /// Returns the chain of unevaluated thunks that are needed first to evaluate this thunk,
+    /// i.e. the thunk that this thunk needs first, the thunk that one needs first and so on.
+    fn needed_thunks(&self) -> Vec<Thunk> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        seen.insert(self.state.as_ptr());
+        let mut next = self.needed_thunk();
+        while let Some(thunk) = next {
+            // A thunk that needs itself is left to the evaluation, which reports the cycle.
+            if !seen.insert(thunk.state.as_ptr()) {
+                break;
+            }
+            next = thunk.needed_thunk();
+            chain.push(thunk);
+        }
+        chain
+    }
+
+    /// Returns the unevaluated thunk that is needed first to evaluate this thunk, if it is known
+    /// without evaluating anything.
+    fn needed_thunk(&self) -> Option<Thunk> {
+        match *self.state.borrow() {
+            ThunkState::Unevaluated{ref expr, ref env, ..} => match needs(expr, env) {
+                Needs::Thunk(thunk) => Some(thunk),
+                Needs::Nothing | Needs::Unknown => None,
+            },
+            ThunkState::InProgress(_) | ThunkState::Evaluated(_) => None,
+        }
+    }
+}
+
+/// What has to be evaluated first to evaluate an expression.
+enum Needs {
+    /// The expression can be evaluated without evaluating any thunk.
+    Nothing,
+
+    /// The thunk is the first thing that has to be evaluated.
+    Thunk(Thunk),
+
+    /// It is not known without evaluating a part of the expression.
+    Unknown,
+}
+
+/// Returns what has to be evaluated first to evaluate the expression in the environment.
+/// Only the parts of the expression that are always evaluated first are looked at.
+fn needs(expr: &Ast, env: &Env) -> Needs {
+    match expr {
+        &Ast::Val(_) => Needs::Nothing,
+        &Ast::Id(ref id) => match env.get_thunk(&id.1) {
+            Some(ref thunk) if thunk.is_evaluated() => Needs::Nothing,
+            Some(thunk) => {
+                if let ThunkState::Unevaluated{..} = *thunk.state.borrow() {
+                    return Needs::Thunk(thunk.clone());
+                }
+                Needs::Unknown
+            },
+            None => Needs::Unknown,
+        },
+        &Ast::BinOp(ref op, ref left, ref right) => match needs(left, env) {
+            // The right operand of `and` and `or` is only evaluated depending on the left one.
+            Needs::Nothing if *op != BinOp::And && *op != BinOp::Or => needs(right, env),
+            Needs::Nothing => Needs::Unknown,
+            needed => needed,
+        },
+        &Ast::UnaryOp(_, ref expr) => needs(expr, env),
+        &Ast::Field(ref expr, _) | &Ast::If(ref expr, ..) | &Ast::Call(ref expr, _) => {
+            match needs(expr, env) {
+                Needs::Nothing => Needs::Unknown,
+                needed => needed,
+            }
+        },
+        _ => Needs::Unknown,
+    }
+}
+
+impl PartialEq for Thunk {
+    /// Evaluated thunks are equal if their results are equal. Unevaluated thunks are equal if
+    /// they have the same expression in the same environment and file.
+    fn eq(&self, other: &Thunk) -> bool {
+        if Rc::ptr_eq(&self.state, &other.state) {
+            return true;
+        }
+
+        match (&*self.state.borrow(), &*other.state.borrow()) {
+            (&ThunkState::Evaluated(ref l), &ThunkState::Evaluated(ref r)) => l == r,
+            (&ThunkState::Unevaluated{expr: ref lexpr, env: ref lenv, file_path: ref lfile, out_path: ref lout, ..},
+             &ThunkState::Unevaluated{expr: ref rexpr, env: ref renv, file_path: ref rfile, out_path: ref rout, ..}) =>
+                lexpr == rexpr && lenv == renv && lfile == rfile && lout == rout,
+            _ => false,
+        }
+    }
+}
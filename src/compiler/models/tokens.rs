@@ -13,10 +13,26 @@ pub enum Token {
     GroupR(usize),
     BlockL(usize),
     BlockR(usize),
+    ListL(usize),
+    ListR(usize),
     Comma(usize),
     Colon(usize),
     Assign(usize),
     Semi(usize),
+    Dot(usize),
+}
+impl Token {
+    /// The position of the token in the code.
+    pub fn pos(&self) -> usize {
+        match self {
+            &Token::BinOp(pos, _) | &Token::UnaryOp(pos, _) | &Token::Val(pos, _) |
+            &Token::Keyword(pos, _) | &Token::Id(pos, _) => pos,
+            &Token::GroupL(pos) | &Token::GroupR(pos) | &Token::BlockL(pos) |
+            &Token::BlockR(pos) | &Token::ListL(pos) | &Token::ListR(pos) |
+            &Token::Comma(pos) | &Token::Colon(pos) | &Token::Assign(pos) |
+            &Token::Semi(pos) | &Token::Dot(pos) => pos,
+        }
+    }
 }
 
 #[derive(PartialEq)]
@@ -30,6 +46,13 @@ pub enum BinOp {
     Mod,
     Pow,
     Equals,
+    NotEquals,
+    Lt,
+    Gt,
+    LtEq,
+    GtEq,
+    And,
+    Or,
 }
 
 impl Display for BinOp {
@@ -42,6 +65,13 @@ impl Display for BinOp {
             &BinOp::Mod => write!(f, "%"),
             &BinOp::Pow => write!(f, "**"),
             &BinOp::Equals => write!(f, "=="),
+            &BinOp::NotEquals => write!(f, "!="),
+            &BinOp::Lt => write!(f, "<"),
+            &BinOp::Gt => write!(f, ">"),
+            &BinOp::LtEq => write!(f, "<="),
+            &BinOp::GtEq => write!(f, ">="),
+            &BinOp::And => write!(f, "and"),
+            &BinOp::Or => write!(f, "or"),
         }
     }
 }
@@ -53,6 +83,14 @@ pub enum UnaryOp {
     Not
 }
 
+impl Display for UnaryOp {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &UnaryOp::Not => write!(f, "!"),
+        }
+    }
+}
+
 #[derive(PartialEq)]
 #[derive(Debug)]
 #[derive(Clone)]
@@ -83,7 +121,11 @@ pub enum Keyword {
     Let,
     Fn,
     Import,
+    Collection,
     Set,
     In,
     Html,
+    If,
+    Then,
+    Else,
 }
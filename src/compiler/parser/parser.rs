@@ -1,3 +1,4 @@
+use std::iter::Peekable;
 use helpers::all_ok;
 
 use compiler::models::{Ast, Id, Token, BinOp, Val, Keyword};
@@ -7,18 +8,23 @@ use compiler::errors::ParseError;
 use super::parsers::{
     parse_keyword,
     parse_binop,
+    parse_unaryop,
     parse_call,
+    parse_field_access,
+    parse_list,
 };
 
 pub type ParseResult = Result<Ast, ParseError>;
 
 pub struct Parser<'i> {
-    pub token_iter: &'i mut TokenIterator<'i>,
+    /// The tokens to be parsed. Peekable so that parsers can look at the next token without
+    /// consuming it.
+    pub token_iter: Peekable<&'i mut TokenIterator<'i>>,
 }
 impl<'i> Parser<'i> {
 
     pub fn new(token_iter: &'i mut TokenIterator<'i>) -> Self {
-        Parser{token_iter: token_iter}
+        Parser{token_iter: token_iter.peekable()}
     }
 
     fn parse_token(&mut self, token: TokenResult) -> Option<ParseResult> {
@@ -26,8 +32,10 @@ impl<'i> Parser<'i> {
             Ok(Token::Val(_, val)) => all_ok(Ast::Val(val)),
             Ok(Token::Id(pos, name)) => all_ok(Ast::Id(Id(pos, name))),
             Ok(Token::BinOp(pos, op)) => parse_binop(self, op, pos),
+            Ok(Token::UnaryOp(pos, op)) => parse_unaryop(self, op, pos),
             Ok(Token::Keyword(pos, keyword)) => parse_keyword(self, keyword, pos),
             Ok(Token::GroupL(pos)) => parse_call(self, pos),
+            Ok(Token::ListL(pos)) => parse_list(self, pos),
             Ok(t) => Some(Err(ParseError::Unexpected(t))),
             Err(err) => Some(Err(ParseError::Lexer(err))),
         }
@@ -38,7 +46,10 @@ impl<'i> Iterator for Parser<'i> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(token) = self.token_iter.next() {
-            self.parse_token(token)
+            match self.parse_token(token) {
+                Some(Ok(expr)) => parse_field_access(self, expr),
+                other => other,
+            }
         } else {
             None
         }
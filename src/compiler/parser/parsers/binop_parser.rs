@@ -1,6 +1,7 @@
 use compiler::models::{Ast, BinOp};
 use compiler::parser::{ParseResult, Parser};
 use compiler::errors::ParseError;
+use std::rc::Rc;
 
 pub fn parse_binop(parser: &mut Parser, op: BinOp, pos: usize) -> Option<ParseResult> {
     // Get the left expression
@@ -10,7 +11,7 @@ pub fn parse_binop(parser: &mut Parser, op: BinOp, pos: usize) -> Option<ParseRe
     let right = expect_expression!(parser, pos);
 
     Some(Ok(Ast::BinOp(op, 
-                       Box::new(left), 
-                       Box::new(right))))
+                       Rc::new(left), 
+                       Rc::new(right))))
 }
 
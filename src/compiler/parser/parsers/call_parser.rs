@@ -1,12 +1,32 @@
-use compiler::models::{Ast, Token};
+use compiler::models::{Ast, Arg, Token};
 use compiler::parser::{ParseResult, Parser};
 use compiler::errors::ParseError;
+use std::rc::Rc;
 
+/// Parses `(func arg1 arg2 ...)`.
 pub fn parse_call(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
     let func = expect_expression!(parser, pos);
-    let param = expect_expression!(parser, pos);
-    expect_group_r!(parser.token_iter, pos);
-    Some(Ok(Ast::Call(
-            Box::new(func),
-            Box::new(param))))
+    let mut args = Vec::new();
+
+    loop {
+        let arg_pos = match parser.token_iter.peek() {
+            Some(&Ok(Token::GroupR(_))) => None,
+            Some(&Ok(ref token)) => Some(token.pos()),
+            _ => Some(pos),
+        };
+
+        let arg_pos = match arg_pos {
+            Some(arg_pos) => arg_pos,
+            None => {
+                let end_pos = expect_group_r!(parser.token_iter, pos);
+                if args.is_empty() {
+                    return Some(Err(ParseError::ExpectedArgument(end_pos)));
+                }
+                return Some(Ok(Ast::Call(Rc::new(func), args)));
+            }
+        };
+
+        let arg = expect_expression!(parser, pos);
+        args.push(Arg(arg_pos, Rc::new(arg)));
+    }
 }
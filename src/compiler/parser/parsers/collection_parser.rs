@@ -0,0 +1,15 @@
+use compiler::models::{Ast, Token, Val};
+use compiler::parser::{ParseResult, Parser};
+use compiler::errors::ParseError;
+
+pub fn parse_collection(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
+    let token = next_token!(parser.token_iter, pos);
+    match token {
+        Token::Val(pos, Val::Path(dir)) => {
+            Some(Ok(Ast::Collection(pos, dir)))
+        },
+        token => {
+            Some(Err(ParseError::ExpectedPath(token)))
+        }
+    }
+}
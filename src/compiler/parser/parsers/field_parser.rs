@@ -0,0 +1,17 @@
+use helpers::all_ok;
+use compiler::models::{Ast, Id, Token};
+use compiler::parser::{ParseResult, Parser};
+use compiler::errors::ParseError;
+use std::rc::Rc;
+
+/// Parses any field accesses (e.g. `.title.text`) that follow the already parsed expression
+/// `expr`.
+pub fn parse_field_access(parser: &mut Parser, expr: Ast) -> Option<ParseResult> {
+    let mut expr = expr;
+    while let Some(&Ok(Token::Dot(pos))) = parser.token_iter.peek() {
+        parser.token_iter.next();
+        let (pos, field_name) = expect_id!(parser.token_iter, pos);
+        expr = Ast::Field(Rc::new(expr), Id(pos, field_name));
+    }
+    all_ok(expr)
+}
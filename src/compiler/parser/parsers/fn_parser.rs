@@ -1,20 +1,33 @@
 use helpers::all_ok;
-use compiler::models::{Ast, SetField, Token, Keyword};
+use compiler::models::{Ast, Token};
 use compiler::parser::{ParseResult, Parser};
 use compiler::errors::ParseError;
+use std::rc::Rc;
 
+/// Parses `fn a b c: expr`.
+/// Functions with several parameters are turned into nested functions with one parameter each,
+/// i.e. `fn a b: expr` becomes `fn a: fn b: expr`.
 pub fn parse_fn(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
+    // There has to be at least one parameter.
     let (pos, arg_name) = expect_id!(parser.token_iter, pos);
-    let token = next_token!(parser.token_iter, pos);
+    let mut params = vec![arg_name];
 
-    let pos = match token {
-        Token::Colon(pos) => pos,
-        token => {
-            return Some(Err(ParseError::ExpectedColon(token)));
+    let pos = loop {
+        let token = next_token!(parser.token_iter, pos);
+        match token {
+            Token::Id(_, arg_name) => params.push(arg_name),
+            Token::Colon(pos) => { break pos; },
+            token => {
+                return Some(Err(ParseError::ExpectedColon(token)));
+            }
         }
     };
 
     let expr = expect_expression!(parser, pos);
 
-    all_ok(Ast::Fn(arg_name, Box::new(expr)))
+    let func = params
+        .into_iter()
+        .rev()
+        .fold(expr, |expr, param| { Ast::Fn(param, Rc::new(expr)) });
+    all_ok(func)
 }
@@ -2,6 +2,8 @@ use helpers::all_ok;
 use compiler::models::{Ast, SetField, Token};
 use compiler::parser::{ParseResult, Parser};
 use compiler::errors::ParseError;
+use super::parse_list;
+use std::rc::Rc;
 
 pub fn parse_html(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
 
@@ -126,6 +128,7 @@ fn parse_html_child(parser: &mut Parser, _pos: usize, token: Token) -> Option<Re
             all_ok(expr)
         }
         Token::Id(pos, tag_name) => parse_html_tag_contents(parser, tag_name, pos),
+        Token::ListL(pos) => parse_list(parser, pos),
         other_token => Some(Err(ParseError::Unexpected(other_token))),
     }
 }
@@ -135,7 +138,7 @@ fn parse_html_attribute(parser: &mut Parser, tag_name: String, pos: usize) -> Op
     match token {
         Token::Assign(pos) => {
             let expr = expect_expression!(parser, pos);
-            return all_ok(HtmlAttributeParserResult::Success(SetField{name: tag_name, value: expr}));
+            return all_ok(HtmlAttributeParserResult::Success(SetField{name: tag_name, value: Rc::new(expr)}));
         },
         other_token => {
             return all_ok(HtmlAttributeParserResult::NotAttribute{id: tag_name, next_token: other_token});
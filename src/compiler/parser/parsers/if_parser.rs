@@ -0,0 +1,14 @@
+use helpers::all_ok;
+use compiler::models::{Ast, Token, Keyword};
+use compiler::parser::{ParseResult, Parser};
+use compiler::errors::ParseError;
+use std::rc::Rc;
+
+pub fn parse_if(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
+    let cond = expect_expression!(parser, pos);
+    let pos = expect_keyword!(Keyword::Then, parser.token_iter, pos);
+    let then = expect_expression!(parser, pos);
+    let pos = expect_keyword!(Keyword::Else, parser.token_iter, pos);
+    let otherwise = expect_expression!(parser, pos);
+    all_ok(Ast::If(Rc::new(cond), Rc::new(then), Rc::new(otherwise)))
+}
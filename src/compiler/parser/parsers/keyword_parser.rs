@@ -5,8 +5,10 @@ use super::{
     parse_let,
     parse_fn,
     parse_import,
+    parse_collection,
     parse_set,
     parse_html,
+    parse_if,
 };
 
 pub fn parse_keyword(parser: &mut Parser, keyword: Keyword, pos: usize) -> Option<ParseResult> {
@@ -14,8 +16,12 @@ pub fn parse_keyword(parser: &mut Parser, keyword: Keyword, pos: usize) -> Optio
         Keyword::Let => parse_let(parser, pos),
         Keyword::Fn => parse_fn(parser, pos),
         Keyword::Import => parse_import(parser, pos),
+        Keyword::Collection => parse_collection(parser, pos),
         Keyword::Set => parse_set(parser, pos),
         Keyword::In => Some(Err(ParseError::UnexpectedKeyword(Keyword::In))),
         Keyword::Html => parse_html(parser, pos),
+        Keyword::If => parse_if(parser, pos),
+        Keyword::Then => Some(Err(ParseError::UnexpectedKeyword(Keyword::Then))),
+        Keyword::Else => Some(Err(ParseError::UnexpectedKeyword(Keyword::Else))),
     }
 }
@@ -2,20 +2,109 @@ use helpers::all_ok;
 use compiler::models::{Ast, SetField, Token, Keyword};
 use compiler::parser::{ParseResult, Parser};
 use compiler::errors::ParseError;
+use super::parse_set_field;
+use std::rc::Rc;
 
+/// Bindings together with the position of their names.
+type Bindings = Vec<(usize, SetField)>;
 
+/// Parses `let a = .., b = .. in expr` or `let { a = .., b = .. } in expr`.
 pub fn parse_let(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
-    let (pos, id_name) = expect_id!(parser.token_iter, pos);
-    let pos = expect_assignment!(parser.token_iter, pos);
-    let value = expect_expression!(parser, pos);
-    let pos = expect_keyword!(Keyword::In, parser.token_iter, pos);
+    let block_pos = match parser.token_iter.peek() {
+        Some(&Ok(Token::BlockL(block_pos))) => Some(block_pos),
+        _ => None,
+    };
+
+    let result = match block_pos {
+        Some(block_pos) => {
+            // Skip the `{` that was just peeked
+            parser.token_iter.next();
+            parse_bindings(parser, block_pos, is_block_r, ParseError::ExpectedComma)
+        },
+        None => {
+            parse_bindings(parser, pos, is_in, |token| ParseError::ExpectedKeyword(Keyword::In, token))
+        },
+    };
+    let (bindings, closing) = match result {
+        Some(Ok(result)) => result,
+        Some(Err(err)) => { return Some(Err(err)); },
+        None => { return Some(Err(ParseError::UnexpectedEndOfCode(pos))); },
+    };
+
+    // The bindings of a block are followed by `in`
+    let pos = match closing {
+        Token::BlockR(block_pos) => expect_keyword!(Keyword::In, parser.token_iter, block_pos),
+        closing => closing.pos(),
+    };
+
     let expr = expect_expression!(parser, pos);
-    all_ok(
-        Ast::Let(
-            Box::new(SetField {
-                name: id_name,
-                value: value
-            }),
-            Box::new(expr)))
 
+    if let Err(err) = check_duplicates(&bindings) {
+        return Some(Err(err));
+    }
+
+    let bindings = bindings.into_iter().map(|(_, binding)| binding).collect();
+    all_ok(Ast::Let(bindings, Rc::new(expr)))
+}
+
+/// Parses bindings that are separated by commas up to the token that closes them, and returns
+/// the bindings and the closing token.
+///
+/// # Arguments
+/// `is_closing` - returns `true` for the token that closes the bindings.
+/// `unexpected` - returns the error for any other token that follows a binding.
+fn parse_bindings<C, E>(parser: &mut Parser, pos: usize, is_closing: C, unexpected: E) -> Option<Result<(Bindings, Token), ParseError>>
+    where C: Fn(&Token) -> bool, E: Fn(Token) -> ParseError {
+    let mut bindings = vec![];
+    let mut pos = pos;
+
+    loop {
+        match parse_set_field(parser, pos) {
+            Some(Ok((name_pos, binding))) => {
+                pos = name_pos;
+                bindings.push((name_pos, binding));
+            },
+            Some(Err(err)) => { return Some(Err(err)); },
+            None => { return Some(Err(ParseError::UnexpectedEndOfCode(pos))); },
+        }
+
+        let token = next_token!(parser.token_iter, pos);
+        match token {
+            Token::Comma(comma_pos) => { pos = comma_pos; },
+            token => {
+                if is_closing(&token) {
+                    return all_ok((bindings, token));
+                }
+                return Some(Err(unexpected(token)));
+            }
+        }
+    }
+}
+
+fn is_in(token: &Token) -> bool {
+    match token {
+        &Token::Keyword(_, Keyword::In) => true,
+        _ => false,
+    }
+}
+
+fn is_block_r(token: &Token) -> bool {
+    match token {
+        &Token::BlockR(_) => true,
+        _ => false,
+    }
+}
+
+/// Returns `ParseError::DuplicateBinding` if the same name is bound more than once.
+fn check_duplicates(bindings: &Bindings) -> Result<(), ParseError> {
+    for (i, &(pos, ref binding)) in bindings.iter().enumerate() {
+        let first = bindings[..i]
+            .iter()
+            .find(|&&(_, ref other)| other.name == binding.name);
+
+        if let Some(&(first_pos, _)) = first {
+            return Err(ParseError::DuplicateBinding(binding.name.clone(), first_pos, pos));
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,26 @@
+use helpers::all_ok;
+use compiler::models::{Ast, Token};
+use compiler::parser::{ParseResult, Parser};
+use compiler::errors::ParseError;
+
+/// Parses the expressions of a list literal until the closing `]` is found.
+pub fn parse_list(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
+    let mut items = vec![];
+    loop {
+        let is_end = match parser.token_iter.peek() {
+            Some(&Ok(Token::ListR(_))) => true,
+            None => {
+                return Some(Err(ParseError::UnexpectedEndOfCode(pos)));
+            },
+            _ => false,
+        };
+
+        if is_end {
+            parser.token_iter.next();
+            return all_ok(Ast::List(items));
+        }
+
+        let item = expect_expression!(parser, pos);
+        items.push(item);
+    }
+}
@@ -13,11 +13,26 @@ pub use self::fn_parser::parse_fn;
 mod import_parser;
 pub use self::import_parser::parse_import;
 
+mod collection_parser;
+pub use self::collection_parser::parse_collection;
+
 mod set_parser;
-pub use self::set_parser::parse_set;
+pub use self::set_parser::{parse_set, parse_set_field};
 
 mod binop_parser;
 pub use self::binop_parser::parse_binop;
 
+mod unaryop_parser;
+pub use self::unaryop_parser::parse_unaryop;
+
 mod call_parser;
 pub use self::call_parser::parse_call;
+
+mod field_parser;
+pub use self::field_parser::parse_field_access;
+
+mod list_parser;
+pub use self::list_parser::parse_list;
+
+mod if_parser;
+pub use self::if_parser::parse_if;
@@ -2,6 +2,7 @@ use helpers::all_ok;
 use compiler::models::{Token, Keyword, Ast, SetField};
 use compiler::parser::{ParseResult, Parser};
 use compiler::errors::ParseError;
+use std::rc::Rc;
 
 pub fn parse_set(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
     // Get the token
@@ -22,7 +23,7 @@ pub fn parse_set(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
     // Now find all the set fields
     loop {
         let result = parse_set_field(parser, pos);
-        if let Some(Ok(field)) = result {
+        if let Some(Ok((_, field))) = result {
             set_fields.push(field);
 
         } else if let Some(Err(err)) = result {
@@ -50,16 +51,17 @@ pub fn parse_set(parser: &mut Parser, pos: usize) -> Option<ParseResult> {
     Some(Ok(Ast::Set(set_fields)))
 }
 
-fn parse_set_field(parser: &mut Parser, pos: usize) -> Option<Result<SetField, ParseError>> {
+/// Parses `name = expr` and returns the field together with the position of its name.
+pub fn parse_set_field(parser: &mut Parser, pos: usize) -> Option<Result<(usize, SetField), ParseError>> {
 
     // Expect an id token
-    let (pos, field_name) = expect_id!(parser.token_iter, pos);
+    let (name_pos, field_name) = expect_id!(parser.token_iter, pos);
 
     // Expect next token to be '='
-    let pos = expect_assignment!(parser.token_iter, pos);
+    let pos = expect_assignment!(parser.token_iter, name_pos);
 
     // And let the value be any kind of expression
     let value = expect_expression!(parser, pos);
 
-    return Some(Ok(SetField { name: field_name, value: value }));
+    return Some(Ok((name_pos, SetField { name: field_name, value: Rc::new(value) })));
 }
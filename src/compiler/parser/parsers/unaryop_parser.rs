@@ -0,0 +1,10 @@
+use compiler::models::{Ast, UnaryOp};
+use compiler::parser::{ParseResult, Parser};
+use compiler::errors::ParseError;
+use std::rc::Rc;
+
+pub fn parse_unaryop(parser: &mut Parser, op: UnaryOp, pos: usize) -> Option<ParseResult> {
+    let expr = expect_expression!(parser, pos);
+
+    Some(Ok(Ast::UnaryOp(op, Rc::new(expr))))
+}
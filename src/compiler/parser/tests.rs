@@ -1,6 +1,7 @@
 use compiler::parser::*;
 use compiler::models::*;
 use compiler::errors::ParseError;
+use std::rc::Rc;
 
 #[test]
 fn parse_binary_op_test() {
@@ -13,8 +14,8 @@ fn parse_binary_op_test() {
     let expected = vec![
         Ok(Ast::BinOp(
                 BinOp::Add, 
-                Box::new(Ast::Val(Val::Int(3))),
-                Box::new(Ast::Val(Val::Int(4)))
+                Rc::new(Ast::Val(Val::Int(3))),
+                Rc::new(Ast::Val(Val::Int(4)))
                 ))
     ];
 
@@ -37,11 +38,11 @@ fn parse_nested_binary_op_test() {
     let expected = vec![
         Ok(Ast::BinOp(
                 BinOp::Add,
-                Box::new(Ast::BinOp(
+                Rc::new(Ast::BinOp(
                         BinOp::Sub,
-                        Box::new(Ast::Val(Val::Int(1))),
-                        Box::new(Ast::Val(Val::Int(2))))),
-                        Box::new(Ast::Val(Val::Int(3)))))
+                        Rc::new(Ast::Val(Val::Int(1))),
+                        Rc::new(Ast::Val(Val::Int(2))))),
+                        Rc::new(Ast::Val(Val::Int(3)))))
     ];
 
     let mut iter = input.iter().map(Clone::clone);
@@ -63,11 +64,11 @@ fn parse_nested_binary_op_second_order() {
     let expected = vec![
         Ok(Ast::BinOp(
                 BinOp::Add,
-                Box::new(Ast::Val(Val::Int(1))),
-                Box::new(Ast::BinOp(
+                Rc::new(Ast::Val(Val::Int(1))),
+                Rc::new(Ast::BinOp(
                         BinOp::Sub,
-                        Box::new(Ast::Val(Val::Int(2))),
-                        Box::new(Ast::Val(Val::Int(3)))))))
+                        Rc::new(Ast::Val(Val::Int(2))),
+                        Rc::new(Ast::Val(Val::Int(3)))))))
     ];
 
     let mut iter = input.iter().map(Clone::clone);
@@ -98,11 +99,11 @@ fn test_set_construction() {
         Ok(Ast::Set( vec![ 
                      SetField { 
                          name: "field1".to_string(),
-                         value: Ast::Val(Val::String("value".to_string()))
+                         value: Rc::new(Ast::Val(Val::String("value".to_string())))
                      },
                      SetField { 
                          name: "field2".to_string(),
-                         value: Ast::Val(Val::Int(23))
+                         value: Rc::new(Ast::Val(Val::Int(23)))
                      },
         ]))
     ];
@@ -132,15 +133,15 @@ fn parse_let_test() {
 
     let expected = vec![
         Ok(Ast::Let(
-                Box::new(
+                vec![
                     SetField {
                         name: "x".to_string(),
-                        value: Ast::Val(Val::Int(2))
-                    }),
-                    Box::new(Ast::BinOp(
+                        value: Rc::new(Ast::Val(Val::Int(2)))
+                    }],
+                    Rc::new(Ast::BinOp(
                             BinOp::Add,
-                            Box::new(Ast::Id(Id(0, "x".to_string()))),
-                            Box::new(Ast::Val(Val::Int(1)))))))
+                            Rc::new(Ast::Id(Id(0, "x".to_string()))),
+                            Rc::new(Ast::Val(Val::Int(1)))))))
     ];
 
     let mut iter = input.iter().map(Clone::clone);
@@ -165,6 +166,42 @@ fn parse_import_should_work_with_string() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn parse_collection_should_work_with_path() {
+    // collection <posts>
+    let input = vec![
+        Ok(Token::Keyword(0, Keyword::Collection)),
+        Ok(Token::Val(11, Val::Path("posts".to_string()))),
+    ];
+
+    let expected = vec![
+        Ok(Ast::Collection(11, "posts".to_string())),
+    ];
+    
+    let mut iter = input.iter().map(Clone::clone);
+    let actual: Vec<_> = Parser::new(&mut iter).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn parse_collection_should_not_work_with_string() {
+    // collection "posts"
+    let input = vec![
+        Ok(Token::Keyword(0, Keyword::Collection)),
+        Ok(Token::Val(11, Val::String("posts".to_string()))),
+    ];
+
+    let expected = vec![
+        Err(ParseError::ExpectedPath(
+                Token::Val(11, Val::String("posts".to_string()))
+                )),
+    ];
+    
+    let mut iter = input.iter().map(Clone::clone);
+    let actual: Vec<_> = Parser::new(&mut iter).collect();
+    assert_eq!(expected, actual);
+}
+
 #[test]
 fn parse_import_should_not_work_with_path() {
     // import <path/to/file>
@@ -198,9 +235,9 @@ fn parse_fn_test() {
 
     let expected = vec![
         Ok(Ast::Fn("x".to_string(), 
-                   Box::new(Ast::BinOp(BinOp::Add,
-                                       Box::new(Ast::Id(Id(8, "x".to_string()))),
-                                       Box::new(Ast::Val(Val::Int(1)))))))
+                   Rc::new(Ast::BinOp(BinOp::Add,
+                                       Rc::new(Ast::Id(Id(8, "x".to_string()))),
+                                       Rc::new(Ast::Val(Val::Int(1)))))))
     ];
 
     let mut iter = input.iter().map(Clone::clone);
@@ -220,8 +257,8 @@ fn parse_function_call() {
 
     let expected = vec![
         Ok(Ast::Call(
-                Box::new(Ast::Id(Id(1, "myFunc".to_string()))),
-                Box::new(Ast::Val(Val::String("test".to_string())))
+                Rc::new(Ast::Id(Id(1, "myFunc".to_string()))),
+                vec![Arg(8, Rc::new(Ast::Val(Val::String("test".to_string()))))]
                 )
             )
     ];
@@ -231,6 +268,69 @@ fn parse_function_call() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn parse_function_with_several_parameters() {
+    // fn x y: x
+    let input = vec![
+        Ok(Token::Keyword(0, Keyword::Fn)),
+        Ok(Token::Id(3, "x".to_string())),
+        Ok(Token::Id(5, "y".to_string())),
+        Ok(Token::Colon(6)),
+        Ok(Token::Id(8, "x".to_string())),
+    ];
+
+    let expected = vec![
+        Ok(Ast::Fn("x".to_string(),
+                   Rc::new(Ast::Fn("y".to_string(),
+                                    Rc::new(Ast::Id(Id(8, "x".to_string())))))))
+    ];
+
+    let mut iter = input.iter().map(Clone::clone);
+    let actual: Vec<_> = Parser::new(&mut iter).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn parse_function_call_with_several_arguments() {
+    // (f 1 2)
+    let input = vec![
+        Ok(Token::GroupL(0)),
+        Ok(Token::Id(1, "f".to_string())),
+        Ok(Token::Val(3, Val::Int(1))),
+        Ok(Token::Val(5, Val::Int(2))),
+        Ok(Token::GroupR(6))
+    ];
+
+    let expected = vec![
+        Ok(Ast::Call(
+                Rc::new(Ast::Id(Id(1, "f".to_string()))),
+                vec![
+                    Arg(3, Rc::new(Ast::Val(Val::Int(1)))),
+                    Arg(5, Rc::new(Ast::Val(Val::Int(2)))),
+                ]))
+    ];
+
+    let mut iter = input.iter().map(Clone::clone);
+    let actual: Vec<_> = Parser::new(&mut iter).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn function_call_without_arguments_should_return_ExpectedArgument() {
+    // (f)
+    let input = vec![
+        Ok(Token::GroupL(0)),
+        Ok(Token::Id(1, "f".to_string())),
+        Ok(Token::GroupR(2))
+    ];
+
+    let expected = vec![Err(ParseError::ExpectedArgument(2))];
+
+    let mut iter = input.iter().map(Clone::clone);
+    let actual: Vec<_> = Parser::new(&mut iter).collect();
+    assert_eq!(expected, actual);
+}
 
 #[test]
 fn parse_html_with_expression() {
@@ -250,8 +350,8 @@ fn parse_html_with_expression() {
 
     let inner_expression = Ast::BinOp(
         BinOp::Add,
-        Box::new(Ast::Val(Val::Int(1))),
-        Box::new(Ast::Val(Val::Int(2))));
+        Rc::new(Ast::Val(Val::Int(1))),
+        Rc::new(Ast::Val(Val::Int(2))));
 
     let expected = vec![
         Ok(Ast::Html{
@@ -349,15 +449,15 @@ fn parse_attributes_should_work() {
                 vec![
                     SetField {
                         name: "class".to_string(),
-                        value: Ast::Val(Val::String("test".to_string())),
+                        value: Rc::new(Ast::Val(Val::String("test".to_string()))),
                     },
                     SetField {
                         name: "id".to_string(),
-                        value: Ast::BinOp(
+                        value: Rc::new(Ast::BinOp(
                             BinOp::Add,
-                            Box::new(Ast::Val(Val::String("div".to_string()))),
-                            Box::new(Ast::Val(Val::Int(1)))
-                        ),
+                            Rc::new(Ast::Val(Val::String("div".to_string()))),
+                            Rc::new(Ast::Val(Val::Int(1)))
+                        )),
                     }
                 ]
         };
@@ -435,3 +535,292 @@ fn should_return_UnexpectedEndOfCode_for_incomplete_html_statement2() {
     let actual: Vec<_> = Parser::new(&mut input).collect();
     assert_eq!(expected, actual);
 }
+
+#[test]
+fn parse_field_access_should_work() {
+    // + config.nav.items 1
+    let input = vec![
+        Token::BinOp(0, BinOp::Add),
+        Token::Id(2, "config".to_string()),
+        Token::Dot(8),
+        Token::Id(9, "nav".to_string()),
+        Token::Dot(12),
+        Token::Id(13, "items".to_string()),
+        Token::Val(19, Val::Int(1)),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![
+        Ok(Ast::BinOp(
+                BinOp::Add,
+                Rc::new(Ast::Field(
+                        Rc::new(Ast::Field(
+                                Rc::new(Ast::Id(Id(2, "config".to_string()))),
+                                Id(9, "nav".to_string()))),
+                        Id(13, "items".to_string()))),
+                Rc::new(Ast::Val(Val::Int(1)))))
+    ];
+
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn field_access_should_return_ExpectedId_if_no_field_name() {
+    // page.1
+    let input = vec![
+        Token::Id(0, "page".to_string()),
+        Token::Dot(4),
+        Token::Val(5, Val::Int(1)),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![Err(ParseError::ExpectedId(Token::Val(5, Val::Int(1))))];
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn parse_list_should_work() {
+    // [a "b" [ ]]
+    let input = vec![
+        Token::ListL(0),
+        Token::Id(1, "a".to_string()),
+        Token::Val(3, Val::String("b".to_string())),
+        Token::ListL(7),
+        Token::ListR(9),
+        Token::ListR(10),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![
+        Ok(Ast::List(vec![
+            Ast::Id(Id(1, "a".to_string())),
+            Ast::Val(Val::String("b".to_string())),
+            Ast::List(vec![]),
+        ]))
+    ];
+
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn parse_list_as_html_child_should_work() {
+    // html! ul [ "a" "b" ]
+    let input = vec![
+        Token::Keyword(0, Keyword::Html),
+        Token::Id(6, "ul".to_string()),
+        Token::ListL(9),
+        Token::Val(11, Val::String("a".to_string())),
+        Token::Val(15, Val::String("b".to_string())),
+        Token::ListR(19),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![
+        Ok(Ast::Html{
+            tag_name: "ul".to_string(),
+            attributes: vec![],
+            children: vec![
+                Ast::List(vec![
+                    Ast::Val(Val::String("a".to_string())),
+                    Ast::Val(Val::String("b".to_string())),
+                ])
+            ],
+        })
+    ];
+
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn should_return_UnexpectedEndOfCode_for_unclosed_list() {
+    let input = vec![
+        Token::ListL(0),
+        Token::Val(1, Val::Int(1)),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![Err(ParseError::UnexpectedEndOfCode(0))];
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn parse_if_should_work() {
+    // if x then 1 else 2
+    let input = vec![
+        Token::Keyword(0, Keyword::If),
+        Token::Id(3, "x".to_string()),
+        Token::Keyword(5, Keyword::Then),
+        Token::Val(10, Val::Int(1)),
+        Token::Keyword(12, Keyword::Else),
+        Token::Val(17, Val::Int(2)),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![
+        Ok(Ast::If(
+                Rc::new(Ast::Id(Id(3, "x".to_string()))),
+                Rc::new(Ast::Val(Val::Int(1))),
+                Rc::new(Ast::Val(Val::Int(2)))))
+    ];
+
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn parse_if_should_return_ExpectedKeyword_without_else() {
+    // if x then 1 2
+    let input = vec![
+        Token::Keyword(0, Keyword::If),
+        Token::Id(3, "x".to_string()),
+        Token::Keyword(5, Keyword::Then),
+        Token::Val(10, Val::Int(1)),
+        Token::Val(12, Val::Int(2)),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![Err(ParseError::ExpectedKeyword(Keyword::Else, Token::Val(12, Val::Int(2))))];
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn parse_unary_op_should_work() {
+    // ! == 1 2
+    let input = vec![
+        Token::UnaryOp(0, UnaryOp::Not),
+        Token::BinOp(2, BinOp::Equals),
+        Token::Val(5, Val::Int(1)),
+        Token::Val(7, Val::Int(2)),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![
+        Ok(Ast::UnaryOp(
+                UnaryOp::Not,
+                Rc::new(Ast::BinOp(
+                        BinOp::Equals,
+                        Rc::new(Ast::Val(Val::Int(1))),
+                        Rc::new(Ast::Val(Val::Int(2)))))))
+    ];
+
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn parse_let_with_multiple_bindings() {
+    // let x = 1, y = x in y
+    let input = vec![
+        Token::Keyword(0, Keyword::Let),
+        Token::Id(4, "x".to_string()),
+        Token::Assign(6),
+        Token::Val(8, Val::Int(1)),
+        Token::Comma(9),
+        Token::Id(11, "y".to_string()),
+        Token::Assign(13),
+        Token::Id(15, "x".to_string()),
+        Token::Keyword(17, Keyword::In),
+        Token::Id(20, "y".to_string()),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![
+        Ok(Ast::Let(
+                vec![
+                    SetField{ name: "x".to_string(), value: Rc::new(Ast::Val(Val::Int(1))) },
+                    SetField{ name: "y".to_string(), value: Rc::new(Ast::Id(Id(15, "x".to_string()))) },
+                ],
+                Rc::new(Ast::Id(Id(20, "y".to_string())))))
+    ];
+
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn parse_let_block_should_work() {
+    // let { x = 1, y = 2 } in y
+    let input = vec![
+        Token::Keyword(0, Keyword::Let),
+        Token::BlockL(4),
+        Token::Id(6, "x".to_string()),
+        Token::Assign(8),
+        Token::Val(10, Val::Int(1)),
+        Token::Comma(11),
+        Token::Id(13, "y".to_string()),
+        Token::Assign(15),
+        Token::Val(17, Val::Int(2)),
+        Token::BlockR(19),
+        Token::Keyword(21, Keyword::In),
+        Token::Id(24, "y".to_string()),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![
+        Ok(Ast::Let(
+                vec![
+                    SetField{ name: "x".to_string(), value: Rc::new(Ast::Val(Val::Int(1))) },
+                    SetField{ name: "y".to_string(), value: Rc::new(Ast::Val(Val::Int(2))) },
+                ],
+                Rc::new(Ast::Id(Id(24, "y".to_string())))))
+    ];
+
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn parse_let_should_return_DuplicateBinding_for_duplicate_names() {
+    // let { x = 1, x = 2 } in x
+    let input = vec![
+        Token::Keyword(0, Keyword::Let),
+        Token::BlockL(4),
+        Token::Id(6, "x".to_string()),
+        Token::Assign(8),
+        Token::Val(10, Val::Int(1)),
+        Token::Comma(11),
+        Token::Id(13, "x".to_string()),
+        Token::Assign(15),
+        Token::Val(17, Val::Int(2)),
+        Token::BlockR(19),
+        Token::Keyword(21, Keyword::In),
+        Token::Id(24, "x".to_string()),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![Err(ParseError::DuplicateBinding("x".to_string(), 6, 13))];
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn parse_let_should_return_UnexpectedEndOfCode_after_the_last_binding_of_an_unclosed_block() {
+    // let { x = 1, y = 2
+    let input = vec![
+        Token::Keyword(0, Keyword::Let),
+        Token::BlockL(4),
+        Token::Id(6, "x".to_string()),
+        Token::Assign(8),
+        Token::Val(10, Val::Int(1)),
+        Token::Comma(11),
+        Token::Id(13, "y".to_string()),
+        Token::Assign(15),
+        Token::Val(17, Val::Int(2)),
+    ];
+    let mut input = input.iter().map(Clone::clone).map(Ok);
+
+    let expected = vec![Err(ParseError::UnexpectedEndOfCode(13))];
+    let actual: Vec<_> = Parser::new(&mut input).collect();
+    assert_eq!(expected, actual);
+}
@@ -1,7 +1,25 @@
 use super::tokenizer::Tokenizer;
 use super::parser::Parser;
-use super::evaluator::{Evaluator, Scope, OpenScope};
-use super::models::Output;
+use std::rc::Rc;
+use super::evaluator::{Evaluator, EvalResult, Env, Context};
+use super::models::{BinOp, Output};
+use super::errors::EvalError;
+
+fn eval_str(input: &str) -> EvalResult {
+    let mut tokenizer = Tokenizer::new(input);
+    let mut parser = Parser::new(&mut tokenizer);
+    let ast = parser.next().unwrap().unwrap();
+
+    Evaluator::without_files(&ast, Env::new()).eval()
+}
+
+fn eval_str_with_max_depth(input: &str, max_depth: usize) -> EvalResult {
+    let mut tokenizer = Tokenizer::new(input);
+    let mut parser = Parser::new(&mut tokenizer);
+    let ast = parser.next().unwrap().unwrap();
+
+    Evaluator::with_context(&ast, Env::new(), None, None, Rc::new(Context::new(max_depth))).eval()
+}
 
 #[test]
 fn trivial_test() {
@@ -12,7 +30,292 @@ fn trivial_test() {
     let mut parser = Parser::new(&mut tokenizer);
     let ast = parser.next().unwrap().unwrap();
 
-    let scope = OpenScope::new();
-    let actual = Evaluator::without_files(&ast,  Scope::Open(&scope)).eval();
+    let actual = Evaluator::without_files(&ast, Env::new()).eval();
     assert_eq!(Ok(expected), actual);
 }
+
+#[test]
+fn comparison_operators_work() {
+    assert_eq!(Ok(Output::Bool(true)), eval_str("< 1 2"));
+    assert_eq!(Ok(Output::Bool(false)), eval_str("> 1 2.5"));
+    assert_eq!(Ok(Output::Bool(true)), eval_str("<= 2 2"));
+    assert_eq!(Ok(Output::Bool(true)), eval_str(">= 3.0 2"));
+    assert_eq!(Ok(Output::Bool(true)), eval_str("< \"apple\" \"banana\""));
+    assert_eq!(Ok(Output::Bool(true)), eval_str("!= 1 2"));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn comparing_different_types_should_return_InvalidBinOp() {
+    let expected = Err(EvalError::InvalidBinOp(BinOp::Lt, Output::Int(1), Output::String("a".to_string())));
+    assert_eq!(expected, eval_str("< 1 \"a\""));
+}
+
+#[test]
+fn logical_operators_work() {
+    assert_eq!(Ok(Output::Bool(false)), eval_str("and true false"));
+    assert_eq!(Ok(Output::Bool(true)), eval_str("or false true"));
+    assert_eq!(Ok(Output::Bool(true)), eval_str("not false"));
+    assert_eq!(Ok(Output::Bool(false)), eval_str("! == 1 1"));
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    assert_eq!(Ok(Output::Bool(false)), eval_str("and false undefined"));
+    assert_eq!(Ok(Output::Bool(true)), eval_str("or true undefined"));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn logical_operators_should_return_NotABool_for_non_bools() {
+    assert_eq!(Err(EvalError::NotABool(Output::Int(1))), eval_str("and 1 true"));
+    assert_eq!(Err(EvalError::NotABool(Output::Int(1))), eval_str("not 1"));
+}
+
+#[test]
+fn equality_works_for_all_value_types() {
+    assert_eq!(Ok(Output::Bool(true)), eval_str("== \"home\" \"home\""));
+    assert_eq!(Ok(Output::Bool(false)), eval_str("== true false"));
+    assert_eq!(Ok(Output::Bool(true)), eval_str("== 1 1.0"));
+    assert_eq!(Ok(Output::Bool(false)), eval_str("== 1 \"1\""));
+    assert_eq!(Ok(Output::Bool(true)), eval_str("== [1 [\"a\"]] [1 [\"a\"]]"));
+    assert_eq!(Ok(Output::Bool(false)), eval_str("== [1 2] [1]"));
+    assert_eq!(Ok(Output::Bool(true)), eval_str("!= \"a\" \"b\""));
+}
+
+#[test]
+fn equality_of_sets_is_deep() {
+    assert_eq!(Ok(Output::Bool(true)), 
+               eval_str("== set { a = set { b = + 1 1 } } set { a = set { b = 2 } }"));
+    assert_eq!(Ok(Output::Bool(false)), 
+               eval_str("== set { a = set { b = 1 } } set { a = set { b = 2 } }"));
+    assert_eq!(Ok(Output::Bool(false)), eval_str("== set { a = 1 } set { b = 1 }"));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn comparing_functions_should_return_FnComparison() {
+    let actual = eval_str("== fn x: x 1");
+    match actual {
+        Err(EvalError::FnComparison(Output::Fn(_), Output::Int(1))) => {},
+        other => panic!("Expected FnComparison error but got {:?}", other),
+    }
+}
+
+#[test]
+fn powers_work() {
+    assert_eq!(Ok(Output::Int(1024)), eval_str("** 2 10"));
+    assert_eq!(Ok(Output::Double(0.25)), eval_str("** 2 - 0 2"));
+    assert_eq!(Ok(Output::Double(1.5)), eval_str("** 2.25 0.5"));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn overflowing_operations_should_return_ArithmeticOverflow() {
+    let max = ::std::i64::MAX;
+    let expected = Err(EvalError::ArithmeticOverflow(BinOp::Add, Output::Int(max), Output::Int(1)));
+    assert_eq!(expected, eval_str(&format!("+ {} 1", max)));
+
+    let expected = Err(EvalError::ArithmeticOverflow(BinOp::Mul, Output::Int(max), Output::Int(2)));
+    assert_eq!(expected, eval_str(&format!("* {} 2", max)));
+
+    let expected = Err(EvalError::ArithmeticOverflow(BinOp::Pow, Output::Int(10), Output::Int(19)));
+    assert_eq!(expected, eval_str("** 10 19"));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn division_by_zero_should_return_DivisionByZero() {
+    let expected = Err(EvalError::DivisionByZero(BinOp::Div, Output::Int(1), Output::Int(0)));
+    assert_eq!(expected, eval_str("/ 1 0"));
+
+    let expected = Err(EvalError::DivisionByZero(BinOp::Mod, Output::Int(1), Output::Int(0)));
+    assert_eq!(expected, eval_str("% 1 0"));
+}
+
+#[test]
+fn let_bindings_can_refer_to_themselves() {
+    let input = "let f = fn done: if done then \"done\" else (f true) in (f false)";
+    assert_eq!(Ok(Output::String("done".to_string())), eval_str(input));
+}
+
+#[test]
+fn let_bindings_can_be_mutually_recursive() {
+    let input = "
+        let is_even = fn x: if x then \"even\" else (is_odd true),
+            is_odd = fn x: if x then \"odd\" else (is_even true)
+        in (is_even false)";
+    assert_eq!(Ok(Output::String("odd".to_string())), eval_str(input));
+}
+
+#[test]
+fn let_bindings_can_use_later_bindings_that_shadow_outer_variables() {
+    let input = "let x = 1 in let y = + x 1, x = 10 in y";
+    assert_eq!(Ok(Output::Int(11)), eval_str(input));
+}
+
+#[test]
+fn recursive_functions_can_walk_trees() {
+    let input = "
+        let render = fn node: html! li { (node.title) ul (((map render) node.children)) } 
+        in (render set { 
+            title = \"a\", 
+            children = [set { title = \"b\", children = [] }] 
+        })";
+    let expected = "<li>a<ul><li>b<ul></ul></li></ul></li>";
+    assert_eq!(Ok(Output::String(expected.to_string())), eval_str(input));
+}
+
+#[test]
+fn let_block_bindings_can_see_each_other() {
+    let input = "
+        let { 
+            title = + site \" - Home\", 
+            site = \"My site\" 
+        } in title";
+    assert_eq!(Ok(Output::String("My site - Home".to_string())), eval_str(input));
+}
+
+#[test]
+fn functions_can_take_several_arguments() {
+    let input = "let add3 = fn a b c: + a + b c in (add3 1 2 3)";
+    assert_eq!(Ok(Output::Int(6)), eval_str(input));
+}
+
+#[test]
+fn functions_with_several_parameters_can_be_partially_applied() {
+    let input = "let add = fn a b: + a b in ((map (add 10)) [1 2])";
+    let expected = Output::List(vec![Output::Int(11), Output::Int(12)]);
+    assert_eq!(Ok(expected), eval_str(input));
+}
+
+#[test]
+fn calling_with_too_many_arguments_reports_position() {
+    let input = "let id = fn x: x in (id 1 2)";
+    assert_eq!(Err(EvalError::TooManyArguments(26, Output::Int(1))), eval_str(input));
+}
+
+#[test]
+fn calling_with_too_many_arguments_reports_the_first_surplus_argument() {
+    let input = "let id = fn x: x in (id 1 2 3)";
+    assert_eq!(Err(EvalError::TooManyArguments(26, Output::Int(1))), eval_str(input));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn calling_the_result_of_a_separate_call_returns_NotAFunction() {
+    assert_eq!(Err(EvalError::NotAFunction(Output::Int(1))), eval_str("let id = fn x: x in ((id 1) 2)"));
+    let input = "let id = fn x: x, one = (id 1) in (one 2)";
+    assert_eq!(Err(EvalError::NotAFunction(Output::Int(1))), eval_str(input));
+}
+
+#[test]
+fn nested_bindings_are_evaluated_once() {
+    // Every binding refers to the previous binding twice. Without memoization the last binding
+    // would take 2^40 evaluations.
+    let mut input = "let a0 = 1 in ".to_string();
+    for i in 1..41 {
+        input.push_str(&format!("let a{} = - + a{} a{} a{} in ", i, i - 1, i - 1, i - 1));
+    }
+    input.push_str("a40");
+    assert_eq!(Ok(Output::Int(1)), eval_str(&input));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn infinite_recursion_returns_RecursionLimit() {
+    // The recursive call is not in tail position, so every call uses more stack.
+    let input = "let f = fn x: + 1 (f x) in (f 1)";
+    match eval_str_with_max_depth(input, 50) {
+        Err(EvalError::RecursionLimit(frames)) => {
+            assert!(frames.len() > 1);
+            assert_eq!("f at 30", frames[0]);
+            assert!(frames[1..].iter().all(|frame| frame == "f at 21"));
+        },
+        other => panic!("Expected RecursionLimit but got {:?}", other),
+    }
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn self_referencing_binding_returns_ValueCycle() {
+    let input = "let x = + x 1 in x";
+    assert_eq!(Err(EvalError::ValueCycle("+ x 1".to_string())), eval_str_with_max_depth(input, 50));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn mutually_referencing_bindings_return_ValueCycle() {
+    let input = "let a = set { b = c.d }, c = set { d = a.b } in a.b";
+    assert_eq!(Err(EvalError::ValueCycle("c.d".to_string())), eval_str(input));
+}
+
+#[test]
+fn recursion_below_the_limit_works() {
+    let input = "let count = fn x: if == x 0 then 0 else + 1 (count - x 1) in (count 20)";
+    assert_eq!(Ok(Output::Int(20)), eval_str_with_max_depth(input, 200));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn deep_recursion_returns_RecursionLimit_on_a_small_stack() {
+    // Tests run on threads with a small stack, which would overflow long before the limit if
+    // the stack didn't grow.
+    let input = "let f = fn x: if == x 0 then 0 else + 1 (f - x 1) in (f 100000)";
+    match eval_str_with_max_depth(input, 1000) {
+        Err(EvalError::RecursionLimit(_)) => {},
+        other => panic!("Expected RecursionLimit but got {:?}", other),
+    }
+}
+
+#[test]
+fn deep_recursion_below_the_limit_works_on_a_small_stack() {
+    let input = "let f = fn x: if == x 0 then 0 else + 1 (f - x 1) in (f 20000)";
+    assert_eq!(Ok(Output::Int(20000)), eval_str_with_max_depth(input, 100000));
+}
+
+#[test]
+fn tail_calls_use_constant_stack() {
+    let input = "
+        let count = fn n: if == n 0 then \"done\" else (count - n 1)
+        in (count 1000000)";
+    assert_eq!(Ok(Output::String("done".to_string())), eval_str_with_max_depth(input, 100));
+}
+
+#[test]
+fn tail_calls_through_let_and_several_arguments_use_constant_stack() {
+    let input = "
+        let count = fn n step: 
+            let next = - n step in
+            if <= next 0 then n else (count next step)
+        in (count 100000 1)";
+    assert_eq!(Ok(Output::Int(1)), eval_str_with_max_depth(input, 100));
+}
+
+#[test]
+fn tail_calls_with_an_accumulator_use_constant_stack() {
+    let input = "
+        let sum = fn n acc: if == n 0 then acc else (sum - n 1 + acc n)
+        in (sum 1000000 0)";
+    assert_eq!(Ok(Output::Int(500000500000)), eval_str_with_max_depth(input, 100));
+}
+
+#[test]
+fn tail_calls_with_an_accumulator_on_the_right_use_constant_stack() {
+    let input = "
+        let sum = fn n acc: if == n 0 then acc else (sum - n 1 + n acc)
+        in (sum 1000000 0)";
+    assert_eq!(Ok(Output::Int(500000500000)), eval_str_with_max_depth(input, 100));
+}
+
+#[test]
+fn unused_arguments_that_fail_are_not_reported() {
+    let input = "let first = fn x y: x in (first 1 / 1 0)";
+    assert_eq!(Ok(Output::Int(1)), eval_str(input));
+}
+
+#[test]
+fn collection_can_be_used_as_a_name() {
+    let input = "let collection = [1 2] in (length collection)";
+    assert_eq!(Ok(Output::Int(2)), eval_str(input));
+}
@@ -239,6 +239,14 @@ mod tests {
             assert_eq!(expected, actual);
         }
 
+        #[test]
+        fn works_with_glob_patterns() {
+            let input = "<posts/**/*.foil> ";
+            let expected = "<posts/**/*.foil>";
+            let actual = match_path(input).unwrap().as_str();
+            assert_eq!(expected, actual);
+        }
+
         #[test]
         fn works_with_backslash() {
             //Strings should allow for standalone backslashes
@@ -52,6 +52,34 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    fn lex_not_or_not_equals(&mut self) -> Option<TokenResult> {
+        if self.char_at(self.pos + 1) == Some('=') {
+            token!(Token::BinOp => BinOp::NotEquals, self => 2)
+        } else {
+            token!(Token::UnaryOp => UnaryOp::Not, self => 1)
+        }
+    }
+
+    /// `<` is either the "less than" operator or the start of a path literal.
+    /// It is an operator if it is followed by `=`, whitespace or the end of the code, since paths
+    /// can't start with whitespace. Otherwise it's a path literal.
+    fn lex_lt_or_pathlit(&mut self) -> Option<TokenResult> {
+        match self.char_at(self.pos + 1) {
+            Some('=') => token!(Token::BinOp => BinOp::LtEq, self => 2),
+            Some(x) if x.is_whitespace() => token!(Token::BinOp => BinOp::Lt, self => 1),
+            None => token!(Token::BinOp => BinOp::Lt, self => 1),
+            _ => self.lex_pathlit(),
+        }
+    }
+
+    fn lex_gt(&mut self) -> Option<TokenResult> {
+        if self.char_at(self.pos + 1) == Some('=') {
+            token!(Token::BinOp => BinOp::GtEq, self => 2)
+        } else {
+            token!(Token::BinOp => BinOp::Gt, self => 1)
+        }
+    }
+
     fn lex_mul_or_pow(&mut self) -> Option<TokenResult> {
         if (self.char_at(self.pos), self.char_at(self.pos + 1)) == (Some('*'), Some('*')) {
             token!(Token::BinOp => BinOp::Pow, self => 2)
@@ -94,8 +122,15 @@ impl<'a> Tokenizer<'a> {
             "let" => Token::Keyword(self.pos, Keyword::Let),
             "fn" => Token::Keyword(self.pos, Keyword::Fn),
             "import" => Token::Keyword(self.pos, Keyword::Import),
+            "collection" if self.path_follows(matched.end()) => Token::Keyword(self.pos, Keyword::Collection),
             "set" => Token::Keyword(self.pos, Keyword::Set),
             "in" => Token::Keyword(self.pos, Keyword::In),
+            "if" => Token::Keyword(self.pos, Keyword::If),
+            "then" => Token::Keyword(self.pos, Keyword::Then),
+            "else" => Token::Keyword(self.pos, Keyword::Else),
+            "and" => Token::BinOp(self.pos, BinOp::And),
+            "or" => Token::BinOp(self.pos, BinOp::Or),
+            "not" => Token::UnaryOp(self.pos, UnaryOp::Not),
             "true" => Token::Val(self.pos, Val::Bool(true)),
             "false" => Token::Val(self.pos, Val::Bool(false)),
             x => Token::Id(self.pos, x.to_string()),
@@ -104,6 +139,15 @@ impl<'a> Tokenizer<'a> {
         Some(Ok(token))
     }
 
+    /// Returns `true` if a path literal follows after the next `len` bytes and any whitespace.
+    /// `collection` is only a keyword if it is followed by a path, so that it can still be used
+    /// as a name.
+    fn path_follows(&self, len: usize) -> bool {
+        let rest = &self.buf[self.pos + len..];
+        let start = end_of_whitespace(rest).unwrap_or(0);
+        match_path(&rest[start..]).is_some()
+    }
+
     fn lex_numlit(&mut self) -> Option<TokenResult> {
         if let Some(mdouble) = match_double(&self.buf[self.pos..]) {
             let d = mdouble.as_str().parse::<f64>().unwrap();
@@ -153,18 +197,22 @@ impl<'a> Iterator for Tokenizer<'a> {
             '-' => token!(Token::BinOp => BinOp::Sub, self=>1),
             '/' => token!(Token::BinOp => BinOp::Div, self=>1),
             '%' => token!(Token::BinOp => BinOp::Mod, self=>1),
-            '!' => token!(Token::UnaryOp => UnaryOp::Not, self=>1),
+            '!' => self.lex_not_or_not_equals(),
             '(' => token!(Token::GroupL, self=>1),
             ')' => token!(Token::GroupR, self=>1),
             '{' => token!(Token::BlockL, self=>1),
             '}' => token!(Token::BlockR, self=>1),
+            '[' => token!(Token::ListL, self=>1),
+            ']' => token!(Token::ListR, self=>1),
             ',' => token!(Token::Comma, self=>1),
             ':' => token!(Token::Colon, self=>1),
             ';' => token!(Token::Semi, self=>1),
+            '.' => token!(Token::Dot, self=>1),
             '=' => self.lex_assign_or_equals(),
             '*' => self.lex_mul_or_pow(),
             '"' => self.lex_strlit(),
-            '<' => self.lex_pathlit(),
+            '<' => self.lex_lt_or_pathlit(),
+            '>' => self.lex_gt(),
             'h' if self.is_html_keyword() => token!(Token::Keyword => Keyword::Html, self=>5),
             x if x.is_alphabetic() => self.lex_bareword(),
             x if x.is_numeric() => self.lex_numlit(),
@@ -199,6 +247,27 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_tokenizer_comparison_ops_work() {
+        let input = "< <= > >= != and or not <path> <";
+
+        let expected = vec![
+            Ok(Token::BinOp(0, BinOp::Lt)),
+            Ok(Token::BinOp(2, BinOp::LtEq)),
+            Ok(Token::BinOp(5, BinOp::Gt)),
+            Ok(Token::BinOp(7, BinOp::GtEq)),
+            Ok(Token::BinOp(10, BinOp::NotEquals)),
+            Ok(Token::BinOp(13, BinOp::And)),
+            Ok(Token::BinOp(17, BinOp::Or)),
+            Ok(Token::UnaryOp(20, UnaryOp::Not)),
+            Ok(Token::Val(24, Val::Path("path".to_string()))),
+            Ok(Token::BinOp(31, BinOp::Lt)),
+        ];
+
+        let actual: Vec<_> = Tokenizer::new(input).collect();
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn test_tokenizer_native_vals_work() {
         let input = "123 123.123 \"string\" <path>";
@@ -230,6 +299,34 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_tokenizer_collection_is_only_a_keyword_before_a_path() {
+        let input = "collection <posts> collection";
+        
+        let expected = vec![
+            Ok(Token::Keyword(0, Keyword::Collection)),
+            Ok(Token::Val(11, Val::Path("posts".to_string()))),
+            Ok(Token::Id(19, "collection".to_string())),
+        ];
+
+        let actual: Vec<_> = Tokenizer::new(input).collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_tokenizer_conditional_keywords_work() {
+        let input = "if then else";
+        
+        let expected = vec![
+            Ok(Token::Keyword(0, Keyword::If)),
+            Ok(Token::Keyword(3, Keyword::Then)),
+            Ok(Token::Keyword(8, Keyword::Else)),
+        ];
+
+        let actual: Vec<_> = Tokenizer::new(input).collect();
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn test_garbage_string() {
         let input = "\"test ";
@@ -276,6 +373,21 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_list_parens() {
+        let input = "[1 [ ]]";
+        let expected = vec![
+            Ok(Token::ListL(0)),
+            Ok(Token::Val(1, Val::Int(1))),
+            Ok(Token::ListL(3)),
+            Ok(Token::ListR(5)),
+            Ok(Token::ListR(6)),
+        ];
+
+        let actual: Vec<_> = Tokenizer::new(input).collect();
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn test_punctuation() {
         let input = ", : ;";
@@ -289,6 +401,21 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_field_access() {
+        let input = "page.nav.items";
+        let expected = vec![
+            Ok(Token::Id(0, "page".to_string())),
+            Ok(Token::Dot(4)),
+            Ok(Token::Id(5, "nav".to_string())),
+            Ok(Token::Dot(8)),
+            Ok(Token::Id(9, "items".to_string())),
+        ];
+
+        let actual: Vec<_> = Tokenizer::new(input).collect();
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn test_tokenizing_html_works() {
         let input = "html! html{ h1 \"test\" }";
@@ -7,6 +7,12 @@ extern crate htmlescape;
 extern crate fs_extra;
 extern crate regex;
 #[macro_use] extern crate lazy_static;
+extern crate serde_json;
+extern crate toml;
+extern crate csv;
+extern crate pulldown_cmark;
+extern crate glob;
+extern crate stacker;
 
 pub mod compiler;
 pub mod helpers;